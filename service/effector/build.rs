@@ -32,6 +32,41 @@ fn main() {
                 modules_dir: Some("../artifacts".to_string()),
             },
         ),
+        (
+            "ipfs_pin".to_string(),
+            ServiceDescription {
+                config_path: "tests/Config_pin.toml".to_string(),
+                modules_dir: Some("../artifacts".to_string()),
+            },
+        ),
+        (
+            "ipfs_http".to_string(),
+            ServiceDescription {
+                config_path: "tests/Config_http.toml".to_string(),
+                modules_dir: Some("../artifacts".to_string()),
+            },
+        ),
+        (
+            "ipfs_http_timeout".to_string(),
+            ServiceDescription {
+                config_path: "tests/Config_http_timeout.toml".to_string(),
+                modules_dir: Some("../artifacts".to_string()),
+            },
+        ),
+        (
+            "ipfs_not_found".to_string(),
+            ServiceDescription {
+                config_path: "tests/Config_not_found.toml".to_string(),
+                modules_dir: Some("../artifacts".to_string()),
+            },
+        ),
+        (
+            "ipfs_ls".to_string(),
+            ServiceDescription {
+                config_path: "tests/Config_ls.toml".to_string(),
+                modules_dir: Some("../artifacts".to_string()),
+            },
+        ),
     ];
 
     let target = std::env::var("CARGO_CFG_TARGET_ARCH").unwrap();
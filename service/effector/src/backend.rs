@@ -0,0 +1,673 @@
+/*
+ * Copyright 2021 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Backends executing IPFS commands: `ipfs` CLI or kubo HTTP RPC API through `curl`.
+//! Backend is selected by `IPFS_BACKEND` env variable in module config, CLI is the default.
+
+use crate::tar;
+
+use types::ErrorKind;
+
+use marine_rs_sdk::marine;
+use marine_rs_sdk::MountedBinaryResult;
+
+use eyre::{Result, WrapErr};
+use multiaddr::{Multiaddr, Protocol};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+const BACKEND_ENV: &str = "IPFS_BACKEND";
+const VAULT_PATH: &str = "/tmp/vault";
+/// curl gives kubo this long past the timeout to report the error before dropping the request.
+const CURL_TIMEOUT_MARGIN_SEC: u64 = 5;
+/// Exit code of curl when the operation timed out.
+const CURL_TIMED_OUT: i32 = 28;
+
+/// IPFS command in a form that can be executed by any backend.
+/// Option names are long names shared by CLI flags and HTTP query parameters.
+pub struct Command {
+    path: Vec<String>,
+    options: Vec<(String, String)>,
+    args: Vec<String>,
+    input_file: Option<String>,
+}
+
+impl Command {
+    pub fn new(path: &[&str]) -> Self {
+        Self {
+            path: path.iter().map(|p| p.to_string()).collect(),
+            options: vec![],
+            args: vec![],
+            input_file: None,
+        }
+    }
+
+    pub fn option(mut self, name: &str, value: impl ToString) -> Self {
+        self.options.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn arg(mut self, arg: String) -> Self {
+        self.args.push(arg);
+        self
+    }
+
+    /// File passed as the last positional argument to CLI or uploaded as multipart body to HTTP API.
+    pub fn input_file(mut self, path: String) -> Self {
+        self.input_file = Some(path);
+        self
+    }
+
+    /// Reject positional arguments that could be taken for flags, e.g. hash `--help`.
+    fn validate(&self) -> Result<()> {
+        if let Some(arg) = self.args.iter().find(|arg| arg.starts_with('-')) {
+            return Err(ErrorKind::InvalidArgument.error(format!(
+                "invalid argument {}: arguments must not start with -",
+                arg
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+pub trait IpfsBackend {
+    /// Execute command and return its raw output.
+    fn execute(&self, cmd: Command, api_multiaddr: &str, timeout_sec: u64) -> Result<Vec<u8>>;
+
+    /// Execute command and save its raw output to `output_path` instead of returning it.
+    fn execute_to_file(
+        &self,
+        cmd: Command,
+        output_path: String,
+        api_multiaddr: &str,
+        timeout_sec: u64,
+    ) -> Result<()>;
+
+    /// Add file to IPFS and return hash of the root.
+    fn add(
+        &self,
+        file_path: String,
+        options: Vec<(String, String)>,
+        api_multiaddr: &str,
+        timeout_sec: u64,
+    ) -> Result<String>;
+
+    /// Download file or directory by hash to `output_path`.
+    fn get(
+        &self,
+        hash: String,
+        output_path: String,
+        api_multiaddr: &str,
+        timeout_sec: u64,
+    ) -> Result<()>;
+}
+
+pub fn get_backend() -> Result<Box<dyn IpfsBackend>> {
+    match std::env::var(BACKEND_ENV).as_deref() {
+        Err(_) | Ok("cli") => Ok(Box::new(CliBackend)),
+        Ok("http") => Ok(Box::new(HttpBackend)),
+        Ok(other) => Err(ErrorKind::NotConfigured.error(format!(
+            "unknown ipfs backend {}, expected cli or http",
+            other
+        ))),
+    }
+}
+
+#[inline]
+fn get_timeout_string(timeout: u64) -> String {
+    format!("{}s", timeout)
+}
+
+fn unwrap_mounted_binary_result(binary: &str, result: MountedBinaryResult) -> Result<Vec<u8>> {
+    if !result.error.is_empty() {
+        return Err(
+            ErrorKind::IpfsCliFailed.error(format!("{} call failed: {}", binary, result.error))
+        );
+    }
+
+    if result.ret_code != 0 {
+        let stderr = into_utf8(result.stderr)?;
+        return Err(classify_error(&stderr).error(format!("{} call failed: {}", binary, stderr)));
+    }
+
+    Ok(result.stdout)
+}
+
+/// Classify error message printed by kubo or curl.
+fn classify_error(message: &str) -> ErrorKind {
+    let message = message.to_lowercase();
+    let contains_any = |patterns: &[&str]| patterns.iter().any(|p| message.contains(p));
+
+    if contains_any(&["context deadline exceeded", "timed out", "timeout"]) {
+        ErrorKind::Timeout
+    } else if contains_any(&[
+        "not found",
+        "no link named",
+        "no key named",
+        "could not resolve name",
+        "no such file",
+    ]) {
+        ErrorKind::NotFound
+    } else if contains_any(&[
+        "connection refused",
+        "cannot connect to the api",
+        "failed to connect",
+        "could not resolve host",
+    ]) {
+        ErrorKind::IpfsUnavailable
+    } else if contains_any(&["invalid cid", "invalid path", "failed to parse cid"]) {
+        ErrorKind::InvalidCid
+    } else if contains_any(&["blocks were corrupt"]) {
+        ErrorKind::IntegrityCheckFailed
+    } else {
+        ErrorKind::IpfsCliFailed
+    }
+}
+
+pub struct CliBackend;
+
+impl IpfsBackend for CliBackend {
+    fn execute(&self, cmd: Command, api_multiaddr: &str, timeout_sec: u64) -> Result<Vec<u8>> {
+        cmd.validate()?;
+        let input_file = cmd.input_file.map(inject_vault_host_path).transpose()?;
+
+        // `--` ends flags, so that positional arguments are never parsed as flags
+        let args: Vec<String> = cmd
+            .path
+            .into_iter()
+            .chain(
+                cmd.options
+                    .into_iter()
+                    .map(|(name, value)| format!("--{}={}", name, value)),
+            )
+            .chain(vec![
+                String::from("--timeout"),
+                get_timeout_string(timeout_sec),
+                String::from("--api"),
+                api_multiaddr.to_string(),
+                String::from("--"),
+            ])
+            .chain(cmd.args)
+            .chain(input_file)
+            .collect();
+
+        log::info!("ipfs args {:?}", args);
+
+        unwrap_mounted_binary_result("ipfs cli", ipfs(args))
+    }
+
+    fn execute_to_file(
+        &self,
+        cmd: Command,
+        output_path: String,
+        api_multiaddr: &str,
+        timeout_sec: u64,
+    ) -> Result<()> {
+        // mounted binaries can't redirect stdout, so the output is written by the effector
+        let output = self.execute(cmd, api_multiaddr, timeout_sec)?;
+        std::fs::write(&output_path, output).wrap_err(format!("failed to write {}", output_path))
+    }
+
+    fn add(
+        &self,
+        file_path: String,
+        options: Vec<(String, String)>,
+        api_multiaddr: &str,
+        timeout_sec: u64,
+    ) -> Result<String> {
+        let cmd = options
+            .into_iter()
+            .fold(Command::new(&["add"]), |cmd, (name, value)| {
+                cmd.option(&name, value)
+            })
+            .option("quieter", true)
+            .input_file(file_path);
+
+        let output = self.execute(cmd, api_multiaddr, timeout_sec)?;
+        Ok(into_utf8(output)?.trim().to_string())
+    }
+
+    fn get(
+        &self,
+        hash: String,
+        output_path: String,
+        api_multiaddr: &str,
+        timeout_sec: u64,
+    ) -> Result<()> {
+        let cmd = Command::new(&["get"])
+            .option("output", inject_vault_host_path(output_path)?)
+            .arg(hash);
+
+        let output = self.execute(cmd, api_multiaddr, timeout_sec)?;
+        log::info!("ipfs get output: {}", String::from_utf8_lossy(&output));
+        Ok(())
+    }
+}
+
+/// Calls kubo HTTP RPC API, e.g. `POST http://127.0.0.1:5001/api/v0/pin/add?arg=<cid>`.
+pub struct HttpBackend;
+
+impl HttpBackend {
+    fn url(
+        &self,
+        path: &[String],
+        options: Vec<(String, String)>,
+        args: Vec<String>,
+        api_multiaddr: &str,
+        timeout_sec: u64,
+    ) -> Result<String> {
+        let query: Vec<String> = args
+            .into_iter()
+            .map(|arg| format!("arg={}", url_encode(&arg)))
+            .chain(
+                options
+                    .into_iter()
+                    .map(|(name, value)| format!("{}={}", name, url_encode(&value))),
+            )
+            .chain(std::iter::once(format!(
+                "timeout={}",
+                get_timeout_string(timeout_sec)
+            )))
+            .collect();
+
+        Ok(format!(
+            "{}/{}?{}",
+            get_api_url(api_multiaddr)?,
+            path.join("/"),
+            query.join("&")
+        ))
+    }
+
+    /// Send POST request, return response body or error message sent by kubo.
+    /// If `output_file` is given, body is saved there instead.
+    /// Timeout is enforced by curl too, in case the API doesn't respond at all.
+    fn request(
+        &self,
+        url: String,
+        form: Vec<String>,
+        output_file: Option<&str>,
+        timeout_sec: u64,
+    ) -> Result<Vec<u8>> {
+        let mut args = vec![
+            String::from("-s"),
+            String::from("-S"),
+            String::from("-X"),
+            String::from("POST"),
+            String::from("-w"),
+            String::from("\n%{http_code}"),
+            String::from("--connect-timeout"),
+            timeout_sec.to_string(),
+            String::from("--max-time"),
+            (timeout_sec + CURL_TIMEOUT_MARGIN_SEC).to_string(),
+        ];
+        for part in form {
+            args.push(String::from("-F"));
+            args.push(part);
+        }
+        if let Some(output_file) = output_file {
+            args.push(String::from("-o"));
+            args.push(inject_vault_host_path(output_file.to_string())?);
+        }
+        args.push(url);
+
+        log::info!("curl args {:?}", args);
+
+        let result = curl(args);
+        if result.ret_code == CURL_TIMED_OUT {
+            return Err(ErrorKind::Timeout
+                .error(format!("curl call timed out after {} seconds", timeout_sec)));
+        }
+        let mut output = unwrap_mounted_binary_result("curl", result)?;
+        let status_start = output
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .ok_or(eyre::eyre!("curl output doesn't contain http status"))?;
+        let status = String::from_utf8_lossy(&output[status_start + 1..]).to_string();
+        output.truncate(status_start);
+
+        if status != "200" {
+            let body = match output_file {
+                Some(output_file) => std::fs::read(output_file).unwrap_or_default(),
+                None => output,
+            };
+            let message = serde_json::from_slice::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|v| v.get("Message")?.as_str().map(|m| m.to_string()))
+                .unwrap_or_else(|| String::from_utf8_lossy(&body).to_string());
+            return Err(classify_error(&message).error(format!(
+                "ipfs http api call failed with status {}: {}",
+                status, message
+            )));
+        }
+
+        Ok(output)
+    }
+}
+
+impl IpfsBackend for HttpBackend {
+    fn execute(&self, cmd: Command, api_multiaddr: &str, timeout_sec: u64) -> Result<Vec<u8>> {
+        cmd.validate()?;
+        let url = self.url(&cmd.path, cmd.options, cmd.args, api_multiaddr, timeout_sec)?;
+        let form = match cmd.input_file {
+            Some(path) => vec![file_part(&inject_vault_host_path(path)?, None)],
+            None => vec![],
+        };
+        self.request(url, form, None, timeout_sec)
+    }
+
+    fn execute_to_file(
+        &self,
+        cmd: Command,
+        output_path: String,
+        api_multiaddr: &str,
+        timeout_sec: u64,
+    ) -> Result<()> {
+        cmd.validate()?;
+        let url = self.url(&cmd.path, cmd.options, cmd.args, api_multiaddr, timeout_sec)?;
+        let form = match cmd.input_file {
+            Some(path) => vec![file_part(&inject_vault_host_path(path)?, None)],
+            None => vec![],
+        };
+        let result = self.request(url, form, Some(&output_path), timeout_sec);
+        if result.is_err() {
+            // don't leave the error response in place of the output
+            if let Err(e) = std::fs::remove_file(&output_path) {
+                log::warn!("failed to remove {}: {}", output_path, e);
+            }
+        }
+        result.map(|_| ())
+    }
+
+    fn add(
+        &self,
+        file_path: String,
+        options: Vec<(String, String)>,
+        api_multiaddr: &str,
+        timeout_sec: u64,
+    ) -> Result<String> {
+        let form = if Path::new(&file_path).is_dir() {
+            // kubo walks directories on the CLI side, so hidden and ignore are applied here
+            let hidden = options.contains(&(String::from("hidden"), String::from("true")));
+            let ignore: Vec<&str> = options
+                .iter()
+                .filter(|(name, _)| name == "ignore")
+                .map(|(_, pattern)| pattern.as_str())
+                .collect();
+            directory_form(&file_path, hidden, &ignore)?
+        } else {
+            vec![file_part(&inject_vault_host_path(file_path)?, None)]
+        };
+
+        let options = options
+            .into_iter()
+            .chain(std::iter::once((
+                String::from("quieter"),
+                String::from("true"),
+            )))
+            .collect();
+        let url = self.url(
+            &[String::from("add")],
+            options,
+            vec![],
+            api_multiaddr,
+            timeout_sec,
+        )?;
+
+        // HTTP API always responds with JSON objects, the last one describes the root
+        let output = self.request(url, form, None, timeout_sec)?;
+        let mut hash = None;
+        for event in serde_json::Deserializer::from_slice(&output).into_iter::<serde_json::Value>()
+        {
+            let event = event.wrap_err("ipfs response parsing failed")?;
+            if let Some(h) = event.get("Hash").and_then(|h| h.as_str()) {
+                hash = Some(h.to_string());
+            }
+        }
+
+        hash.ok_or(eyre::eyre!("Hash field not found in response"))
+    }
+
+    fn get(
+        &self,
+        hash: String,
+        output_path: String,
+        api_multiaddr: &str,
+        timeout_sec: u64,
+    ) -> Result<()> {
+        // HTTP API sends content as a tar stream, so it's unpacked locally
+        let tar_path = format!("{}.tar", output_path);
+        let url = self.url(
+            &[String::from("get")],
+            vec![],
+            vec![hash],
+            api_multiaddr,
+            timeout_sec,
+        )?;
+
+        let result = self
+            .request(url, vec![], Some(&tar_path), timeout_sec)
+            .and_then(|_| tar::unpack(&tar_path, &output_path));
+        if let Err(e) = std::fs::remove_file(&tar_path) {
+            log::warn!("failed to remove {}: {}", tar_path, e);
+        }
+        result
+    }
+}
+
+/// Build multipart form uploading directory `root` the way `ipfs add -r` does:
+/// parts are named by paths relative to the parent of `root`, directories are empty parts
+/// of `application/x-directory` type.
+fn directory_form(root: &str, hidden: bool, ignore: &[&str]) -> Result<Vec<String>> {
+    let root_name = Path::new(root)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or(ErrorKind::InvalidArgument.error(format!("invalid directory path {}", root)))?;
+
+    let mut form = vec![directory_part(&root_name)];
+    walk_directory(Path::new(root), &root_name, hidden, ignore, &mut form)?;
+    Ok(form)
+}
+
+fn walk_directory(
+    dir: &Path,
+    prefix: &str,
+    hidden: bool,
+    ignore: &[&str],
+    form: &mut Vec<String>,
+) -> Result<()> {
+    let mut entries = std::fs::read_dir(dir)
+        .wrap_err(format!("failed to read directory {:?}", dir))?
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if (!hidden && name.starts_with('.')) || ignore.iter().any(|p| glob_match(p, &name)) {
+            continue;
+        }
+
+        let relative_path = format!("{}/{}", prefix, name);
+        let file_type = entry.file_type()?;
+        // curl would follow the link and upload whatever it points to
+        if file_type.is_symlink() {
+            log::warn!("skipping symlink {:?}", entry.path());
+            continue;
+        }
+        if file_type.is_dir() {
+            form.push(directory_part(&relative_path));
+            walk_directory(&entry.path(), &relative_path, hidden, ignore, form)?;
+        } else {
+            let path = entry.path().to_string_lossy().to_string();
+            form.push(file_part(
+                &inject_vault_host_path(path)?,
+                Some(&relative_path),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Form part uploading file at `host_path`. Path is quoted, so that `;` or `,` in it
+/// aren't taken by curl for part attributes.
+fn file_part(host_path: &str, relative_path: Option<&str>) -> String {
+    let quoted = host_path.replace('\\', "\\\\").replace('"', "\\\"");
+    match relative_path {
+        Some(relative_path) => format!(
+            "file=@\"{}\";filename={}",
+            quoted,
+            url_encode(relative_path)
+        ),
+        None => format!("file=@\"{}\"", quoted),
+    }
+}
+
+fn directory_part(relative_path: &str) -> String {
+    format!(
+        "file=@/dev/null;type=application/x-directory;filename={}",
+        url_encode(relative_path)
+    )
+}
+
+/// Match file name against a pattern with `*` and `?` wildcards.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|i| matches(rest, &name[i..])),
+            Some(('?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && matches(rest, &name[1..]),
+        }
+    }
+
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+/// Convert API multiaddr like `/ip4/127.0.0.1/tcp/5001` to `http://127.0.0.1:5001/api/v0`.
+fn get_api_url(api_multiaddr: &str) -> Result<String> {
+    let multiaddr = Multiaddr::from_str(api_multiaddr)
+        .wrap_err(format!("invalid multiaddr: {}", api_multiaddr))?;
+
+    let mut host = None;
+    let mut port = None;
+    for protocol in multiaddr.iter() {
+        match protocol {
+            Protocol::Ip4(ip) => host = Some(ip.to_string()),
+            Protocol::Ip6(ip) => host = Some(format!("[{}]", ip)),
+            Protocol::Dns(name) | Protocol::Dns4(name) | Protocol::Dns6(name) => {
+                host = Some(name.to_string())
+            }
+            Protocol::Tcp(p) => port = Some(p),
+            _ => {}
+        }
+    }
+
+    match (host, port) {
+        (Some(host), Some(port)) => Ok(format!("http://{}:{}/api/v0", host, port)),
+        _ => Err(eyre::eyre!(
+            "api multiaddr {} should contain host and tcp port",
+            api_multiaddr
+        )),
+    }
+}
+
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+pub fn into_utf8(output: Vec<u8>) -> Result<String> {
+    String::from_utf8(output)
+        .map_err(|_| ErrorKind::Utf8.error("stdout or stderr contains non valid UTF8 string"))
+}
+
+/// Turn path in the vault into the path on the host, where IPFS binaries run.
+pub fn inject_vault_host_path(path: String) -> Result<String> {
+    if let Some(stripped) = path.strip_prefix(VAULT_PATH) {
+        let host_vault_path = std::env::var(VAULT_PATH).map_err(|_| {
+            ErrorKind::NotConfigured.error(format!("vault must be mapped to {}", VAULT_PATH))
+        })?;
+        Ok(format!("/{}/{}", host_vault_path, stripped))
+    } else {
+        Ok(path)
+    }
+}
+
+/// Normalize `path` and check that it stays inside the vault of the current particle.
+/// Paths with `..` and symlinks are rejected, since they may point outside of the vault.
+pub fn confine_to_vault(path: &str) -> Result<String> {
+    let particle_id = marine_rs_sdk::get_call_parameters().particle_id;
+    let particle_vault = Path::new(VAULT_PATH).join(particle_id);
+    let outside = || {
+        ErrorKind::PermissionDenied.error(format!(
+            "path {} is outside of the particle vault {}",
+            path,
+            particle_vault.display()
+        ))
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::RootDir => normalized.push("/"),
+            Component::Normal(name) => normalized.push(name),
+            Component::CurDir => {}
+            Component::ParentDir | Component::Prefix(_) => return Err(outside()),
+        }
+    }
+
+    if !normalized.has_root() || !normalized.starts_with(&particle_vault) {
+        return Err(outside());
+    }
+
+    // a symlink anywhere below the vault, not only the last component, can lead out of it
+    let mut prefix = particle_vault.clone();
+    for component in normalized
+        .strip_prefix(&particle_vault)
+        .unwrap()
+        .components()
+    {
+        prefix.push(component);
+        match std::fs::symlink_metadata(&prefix) {
+            Ok(metadata) if metadata.file_type().is_symlink() => return Err(outside()),
+            Ok(_) => {}
+            // nothing below a missing component can exist yet
+            Err(_) => break,
+        }
+    }
+
+    Ok(normalized.to_string_lossy().to_string())
+}
+
+#[marine]
+#[link(wasm_import_module = "host")]
+extern "C" {
+    /// Execute provided cmd as a parameters of ipfs cli, return result.
+    pub fn ipfs(cmd: Vec<String>) -> MountedBinaryResult;
+
+    /// Execute provided cmd as a parameters of curl, return result.
+    pub fn curl(cmd: Vec<String>) -> MountedBinaryResult;
+}
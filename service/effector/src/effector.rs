@@ -16,13 +16,21 @@
 
 #![allow(improper_ctypes)]
 
-use types::{IpfsGetPeerIdResult, IpfsPutResult, IpfsResult};
+use types::{
+    ErrorKind, IpfsBadPin, IpfsCatResult, IpfsCidResult, IpfsDagImportResult, IpfsGetPeerIdResult,
+    IpfsKey, IpfsKeyListResult, IpfsKeyResult, IpfsLsEntry, IpfsLsResult, IpfsNamePublishResult,
+    IpfsNameResolveResult, IpfsPeeringLsResult, IpfsPeeringPeer, IpfsPin, IpfsPinLsResult,
+    IpfsPinVerifyResult, IpfsPutOptions, IpfsPutResult, IpfsRepoGcResult, IpfsRepoStat,
+    IpfsRepoStatResult, IpfsRepoVerifyResult, IpfsResult, IpfsStat, IpfsStatResult, IpfsSwarmPeer,
+    IpfsSwarmPeersResult,
+};
 
 use marine_rs_sdk::marine;
 use marine_rs_sdk::module_manifest;
-use marine_rs_sdk::MountedBinaryResult;
 use marine_rs_sdk::WasmLoggerBuilder;
 
+use crate::backend::{confine_to_vault, get_backend, Command};
+
 use eyre::{Result, WrapErr};
 
 module_manifest!();
@@ -34,95 +42,237 @@ pub fn main() {
         .unwrap();
 }
 
-fn unwrap_mounted_binary_result(result: MountedBinaryResult) -> Result<String> {
+fn execute(cmd: Command, api_multiaddr: String, timeout_sec: u64) -> Result<Vec<u8>> {
+    get_backend()?.execute(cmd, &api_multiaddr, timeout_sec)
+}
+
+fn execute_json(
+    cmd: Command,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> Result<serde_json::Value> {
+    let result = execute(cmd.option("encoding", "json"), api_multiaddr, timeout_sec)?;
+    serde_json::from_slice(&result).wrap_err("ipfs response parsing failed")
+}
+
+#[marine]
+pub fn connect(multiaddr: String, api_multiaddr: String, timeout_sec: u64) -> IpfsResult {
+    log::info!("connect called with multiaddr {}", multiaddr);
+
+    let cmd = Command::new(&["swarm", "connect"]).arg(multiaddr);
+
+    execute(cmd, api_multiaddr, timeout_sec).map(|_| ()).into()
+}
+
+/// List peers connected to the node along with their latency and connection direction.
+#[marine]
+pub fn swarm_peers(api_multiaddr: String, timeout_sec: u64) -> IpfsSwarmPeersResult {
+    log::info!("swarm_peers called");
+
+    let result: Result<Vec<IpfsSwarmPeer>> = try {
+        let cmd = Command::new(&["swarm", "peers"])
+            .option("latency", true)
+            .option("direction", true);
+        let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
+
+        // kubo responds with null instead of an empty list when there are no peers
+        let mut peers = vec![];
+        for peer in result["Peers"].as_array().into_iter().flatten() {
+            let direction = match peer["Direction"].as_u64() {
+                Some(1) => "inbound",
+                Some(2) => "outbound",
+                _ => "unknown",
+            };
+            peers.push(IpfsSwarmPeer {
+                peer_id: get_json_str(peer, "Peer")?,
+                addr: get_json_str(peer, "Addr")?,
+                latency: peer["Latency"].as_str().unwrap_or_default().to_string(),
+                direction: direction.to_string(),
+            });
+        }
+        peers
+    };
+
     result
-        .into_std()
-        .ok_or(eyre::eyre!(
-            "stdout or stderr contains non valid UTF8 string"
-        ))?
-        .map_err(|e| eyre::eyre!("ipfs cli call failed: {}", e))
+        .map_err(|e| ErrorKind::of(&e).error(format!("swarm_peers: {:?}", e)))
+        .into()
 }
 
-#[inline]
-fn get_timeout_string(timeout: u64) -> String {
-    format!("{}s", timeout)
+/// Close connections to the peer at `multiaddr`.
+#[marine]
+pub fn swarm_disconnect(multiaddr: String, api_multiaddr: String, timeout_sec: u64) -> IpfsResult {
+    log::info!("swarm_disconnect called with multiaddr {}", multiaddr);
+
+    let cmd = Command::new(&["swarm", "disconnect"]).arg(multiaddr);
+
+    execute(cmd, api_multiaddr, timeout_sec).map(|_| ()).into()
 }
 
-fn make_cmd_args(args: Vec<String>, api_multiaddr: String, timeout_sec: u64) -> Vec<String> {
-    args.into_iter()
-        .chain(vec![
-            String::from("--timeout"),
-            get_timeout_string(timeout_sec),
-            String::from("--api"),
-            api_multiaddr,
-        ])
-        .collect()
+/// Add peer to the peering set, so that the node keeps connection to it and reconnects on loss.
+/// `multiaddr` must include `/p2p/<peer id>`.
+#[marine]
+pub fn swarm_peering_add(multiaddr: String, api_multiaddr: String, timeout_sec: u64) -> IpfsResult {
+    log::info!("swarm_peering_add called with multiaddr {}", multiaddr);
+
+    let cmd = Command::new(&["swarm", "peering", "add"]).arg(multiaddr);
+
+    execute(cmd, api_multiaddr, timeout_sec).map(|_| ()).into()
 }
 
+/// Remove peer from the peering set, existing connections are kept.
 #[marine]
-pub fn connect(multiaddr: String, api_multiaddr: String, timeout_sec: u64) -> IpfsResult {
-    log::info!("connect called with multiaddr {}", multiaddr);
+pub fn swarm_peering_rm(peer_id: String, api_multiaddr: String, timeout_sec: u64) -> IpfsResult {
+    log::info!("swarm_peering_rm called with peer id {}", peer_id);
+
+    let cmd = Command::new(&["swarm", "peering", "rm"]).arg(peer_id);
+
+    execute(cmd, api_multiaddr, timeout_sec).map(|_| ()).into()
+}
+
+/// List peers in the peering set.
+#[marine]
+pub fn swarm_peering_ls(api_multiaddr: String, timeout_sec: u64) -> IpfsPeeringLsResult {
+    log::info!("swarm_peering_ls called");
+
+    let result: Result<Vec<IpfsPeeringPeer>> = try {
+        let cmd = Command::new(&["swarm", "peering", "ls"]);
+        let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
 
-    let args = vec![String::from("swarm"), String::from("connect"), multiaddr];
-    let cmd = make_cmd_args(args, api_multiaddr, timeout_sec);
+        let mut peers = vec![];
+        for peer in result["Peers"].as_array().into_iter().flatten() {
+            let addrs = peer["Addrs"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|addr| addr.as_str().map(|a| a.to_string()))
+                .collect();
+            peers.push(IpfsPeeringPeer {
+                peer_id: get_json_str(peer, "ID")?,
+                addrs,
+            });
+        }
+        peers
+    };
 
-    unwrap_mounted_binary_result(ipfs(cmd)).map(|_| ()).into()
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("swarm_peering_ls: {:?}", e)))
+        .into()
 }
 
-/// Put file from specified path to IPFS and return its hash.
+/// Put file from specified path to IPFS with the given `ipfs add` options and return its hash.
 #[marine]
-pub fn put(file_path: String, api_multiaddr: String, timeout_sec: u64) -> IpfsPutResult {
-    log::info!("put called with file path {}", file_path);
+pub fn put(
+    file_path: String,
+    options: IpfsPutOptions,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsPutResult {
+    log::info!(
+        "put called with file path {} and options {:?}",
+        file_path,
+        options
+    );
 
+    let file_path = match confine_to_vault(&file_path) {
+        Ok(path) => path,
+        Err(e) => return Err(e).into(),
+    };
     if !std::path::Path::new(&file_path).exists() {
-        return IpfsPutResult {
-            success: false,
-            error: format!("path {} doesn't exist", file_path),
-            hash: "".to_string(),
-        };
+        return Err(ErrorKind::NotFound.error(format!("path {} doesn't exist", file_path))).into();
     }
 
-    let args = vec![
-        String::from("add"),
-        String::from("-Q"),
-        inject_vault_host_path(file_path),
-    ];
-    let cmd = make_cmd_args(args, api_multiaddr, timeout_sec);
+    get_backend()
+        .and_then(|backend| backend.add(file_path, options.to_args(), &api_multiaddr, timeout_sec))
+        .into()
+}
 
-    log::info!("ipfs put args {:?}", cmd);
+/// Put directory from specified path to IPFS recursively and return hash of its root.
+/// Hidden files are skipped unless `hidden` is set, `ignore` contains patterns of names to skip.
+/// Symlinks are always skipped.
+#[marine]
+pub fn put_directory(
+    dir_path: String,
+    wrap_with_directory: bool,
+    hidden: bool,
+    ignore: Vec<String>,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsPutResult {
+    log::info!("put_directory called with path {}", dir_path);
 
-    unwrap_mounted_binary_result(ipfs(cmd))
-        .map(|res| res.trim().to_string())
+    let dir_path = match confine_to_vault(&dir_path) {
+        Ok(path) => path,
+        Err(e) => return Err(e).into(),
+    };
+    let path = std::path::Path::new(&dir_path);
+    if !path.exists() {
+        return Err(ErrorKind::NotFound.error(format!("path {} doesn't exist", dir_path))).into();
+    }
+    if !path.is_dir() {
+        return Err(
+            ErrorKind::InvalidArgument.error(format!("path {} is not a directory", dir_path))
+        )
+        .into();
+    }
+
+    let options = vec![
+        (String::from("recursive"), String::from("true")),
+        (
+            String::from("wrap-with-directory"),
+            wrap_with_directory.to_string(),
+        ),
+        (String::from("hidden"), hidden.to_string()),
+    ]
+    .into_iter()
+    .chain(
+        ignore
+            .into_iter()
+            .map(|pattern| (String::from("ignore"), pattern)),
+    )
+    .collect();
+
+    get_backend()
+        .and_then(|backend| backend.add(dir_path, options, &api_multiaddr, timeout_sec))
         .into()
 }
 
-/// DAG put input to IPFS and return its hash.
+/// DAG put file to IPFS and return CID of the stored node.
+/// `input_codec` is the codec of the file, `store_codec` is the codec the node is stored with.
 #[marine]
-pub fn dag_put(file_path: String, api_multiaddr: String, timeout_sec: u64) -> IpfsPutResult {
-    log::info!("put called with file path {}", file_path);
+pub fn dag_put(
+    file_path: String,
+    input_codec: String,
+    store_codec: String,
+    hash: String,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsPutResult {
+    log::info!("dag_put called with file path {}", file_path);
 
+    let file_path = match confine_to_vault(&file_path) {
+        Ok(path) => path,
+        Err(e) => return Err(e).into(),
+    };
     if !std::path::Path::new(&file_path).exists() {
-        return IpfsPutResult {
-            success: false,
-            error: format!("path {} doesn't exist", file_path),
-            hash: "".to_string(),
-        };
+        return Err(ErrorKind::NotFound.error(format!("path {} doesn't exist", file_path))).into();
     }
 
-    let args = vec![
-        String::from("dag"),
-        String::from("put"),
-        String::from("--input-codec="),
-        String::from("raw"),
-        String::from(file_path)
-    ];
-
-    let cmd = make_cmd_args(args, api_multiaddr, timeout_sec);
+    let result: Result<String> = try {
+        let cmd = Command::new(&["dag", "put"])
+            .option("input-codec", input_codec)
+            .option("store-codec", store_codec)
+            .option("hash", hash)
+            .input_file(file_path);
 
-    log::info!("ipfs put args {:?}", cmd);
+        let response = execute_json(cmd, api_multiaddr, timeout_sec)?;
+        response["Cid"]["/"]
+            .as_str()
+            .ok_or(eyre::eyre!("no cid in dag put response: {}", response))?
+            .to_string()
+    };
 
-    unwrap_mounted_binary_result(ipfs(cmd))
-        .map(|res| res.trim().to_string())
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("dag_put: {:?}", e)))
         .into()
 }
 
@@ -131,54 +281,926 @@ pub fn dag_put(file_path: String, api_multiaddr: String, timeout_sec: u64) -> Ip
 pub fn get(hash: String, file_path: String, api_multiaddr: String, timeout_sec: u64) -> IpfsResult {
     log::info!("get called with hash {}", hash);
 
-    let args = vec![
-        String::from("get"),
-        String::from("-o"),
-        inject_vault_host_path(file_path),
-        hash,
-    ];
-    let cmd = make_cmd_args(args, api_multiaddr, timeout_sec);
+    confine_to_vault(&file_path)
+        .and_then(|file_path| get_backend()?.get(hash, file_path, &api_multiaddr, timeout_sec))
+        .into()
+}
 
-    log::info!("ipfs get args {:?}", cmd);
+/// DAG get node by `hash` and optional IPLD `path` inside it, e.g. `foo/bar`,
+/// encode it with `output_codec` and save to `file_path`.
+#[marine]
+pub fn dag_get(
+    hash: String,
+    path: String,
+    output_codec: String,
+    file_path: String,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsResult {
+    log::info!("dag_get called with hash {} and path {:?}", hash, path);
 
-    unwrap_mounted_binary_result(ipfs(cmd))
-        .map(|output| {
-            log::info!("ipfs get output: {}", output);
-        })
+    let result: Result<()> = try {
+        let file_path = confine_to_vault(&file_path)?;
+        let ipld_path = if path.is_empty() {
+            hash
+        } else {
+            format!("{}/{}", hash, path.trim_start_matches('/'))
+        };
+        let cmd = Command::new(&["dag", "get"])
+            .option("output-codec", output_codec)
+            .arg(ipld_path);
+
+        let output = execute(cmd, api_multiaddr, timeout_sec)?;
+        std::fs::write(&file_path, output).wrap_err(format!("failed to write {}", file_path))?;
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("dag_get: {:?}", e)))
         .into()
 }
 
-/// Get content by provided hash from IPFS.
+/// Export DAG rooted at `hash` as CARv1 and save it to `file_path`.
 #[marine]
-pub fn dag_get(hash: String, file_path: String, api_multiaddr: String, timeout_sec: u64) -> IpfsResult{
-    log::info!("get called with hash {}", hash);
+pub fn dag_export(
+    hash: String,
+    file_path: String,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsResult {
+    log::info!("dag_export called with hash {}", hash);
+
+    let result: Result<()> = try {
+        let file_path = confine_to_vault(&file_path)?;
+        let cmd = Command::new(&["dag", "export"]).arg(hash);
+
+        let output = execute(cmd, api_multiaddr, timeout_sec)?;
+        std::fs::write(&file_path, output).wrap_err(format!("failed to write {}", file_path))?;
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("dag_export: {:?}", e)))
+        .into()
+}
+
+/// Import blocks from CAR file, pin its roots and return them.
+#[marine]
+pub fn dag_import(
+    file_path: String,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsDagImportResult {
+    log::info!("dag_import called with file path {}", file_path);
+
+    let file_path = match confine_to_vault(&file_path) {
+        Ok(path) => path,
+        Err(e) => return Err(e).into(),
+    };
+    if !std::path::Path::new(&file_path).exists() {
+        return Err(ErrorKind::NotFound.error(format!("path {} doesn't exist", file_path))).into();
+    }
+
+    let result: Result<Vec<String>> = try {
+        let cmd = Command::new(&["dag", "import"])
+            .option("encoding", "json")
+            .option("pin-roots", true)
+            .input_file(file_path);
+
+        // one JSON object is emitted per root
+        let output = execute(cmd, api_multiaddr, timeout_sec)?;
+        let mut roots = vec![];
+        for event in serde_json::Deserializer::from_slice(&output).into_iter::<serde_json::Value>()
+        {
+            let event = event.wrap_err("ipfs response parsing failed")?;
+            let root = &event["Root"];
+            if root.is_null() {
+                continue;
+            }
 
-    let args = vec![
-        String::from("dag"),
-        String::from("get"),
-        inject_vault_host_path(file_path),
+            let cid = root["Cid"]["/"]
+                .as_str()
+                .ok_or(eyre::eyre!("no cid in dag import response: {}", event))?;
+            let pin_error = root["PinErrorMsg"].as_str().unwrap_or_default();
+            if !pin_error.is_empty() {
+                Err(ErrorKind::IpfsCliFailed
+                    .error(format!("failed to pin root {}: {}", cid, pin_error)))?;
+            }
+            roots.push(cid.to_string());
+        }
+        roots
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("dag_import: {:?}", e)))
+        .into()
+}
+
+/// Read contents of the file by provided hash directly from IPFS.
+/// Fails if contents are larger than `max_size` bytes.
+#[marine]
+pub fn cat(hash: String, max_size: u64, api_multiaddr: String, timeout_sec: u64) -> IpfsCatResult {
+    log::info!("cat called with hash {}", hash);
+
+    let result: Result<Vec<u8>> = try {
+        // request one extra byte to detect contents exceeding max_size
+        let cmd = Command::new(&["cat"])
+            .option("length", max_size.saturating_add(1))
+            .arg(hash);
+
+        let contents = execute(cmd, api_multiaddr, timeout_sec)?;
+        if contents.len() as u64 > max_size {
+            Err(ErrorKind::InvalidArgument.error(format!(
+                "contents size exceeds maximum of {} bytes",
+                max_size
+            )))?;
+        }
+        contents
+    };
+
+    result.into()
+}
+
+/// Read `length` bytes of the file by provided hash starting at `offset`.
+/// Less bytes are returned if the file ends earlier.
+#[marine]
+pub fn cat_range(
+    hash: String,
+    offset: u64,
+    length: u64,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsCatResult {
+    log::info!(
+        "cat_range called with hash {}, offset {} and length {}",
+        hash,
+        offset,
+        length
+    );
+
+    let cmd = Command::new(&["cat"])
+        .option("offset", offset)
+        .option("length", length)
+        .arg(hash);
+
+    execute(cmd, api_multiaddr, timeout_sec).into()
+}
+
+/// Save `length` bytes of the file by provided hash starting at `offset` to `file_path`.
+/// With `http` backend the bytes are written by `curl` and don't pass through the module.
+#[marine]
+pub fn get_range(
+    hash: String,
+    offset: u64,
+    length: u64,
+    file_path: String,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsResult {
+    log::info!(
+        "get_range called with hash {}, offset {} and length {}",
         hash,
-    ];
+        offset,
+        length
+    );
 
-    let cmd = make_cmd_args(args, api_multiaddr, timeout_sec);
+    let result: Result<()> = try {
+        let file_path = confine_to_vault(&file_path)?;
+        let cmd = Command::new(&["cat"])
+            .option("offset", offset)
+            .option("length", length)
+            .arg(hash);
+        get_backend()?.execute_to_file(cmd, file_path, &api_multiaddr, timeout_sec)?;
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("get_range: {:?}", e)))
+        .into()
+}
+
+/// Get size, type and local availability of UnixFS object by provided hash with `ipfs files stat`.
+#[marine]
+pub fn stat(hash: String, api_multiaddr: String, timeout_sec: u64) -> IpfsStatResult {
+    log::info!("stat called with hash {}", hash);
+
+    files_stat_path(format!("/ipfs/{}", hash), api_multiaddr, timeout_sec)
+        .map_err(|e| ErrorKind::of(&e).error(format!("stat: {:?}", e)))
+        .into()
+}
+
+/// `ipfs files stat` of MFS or `/ipfs/<hash>` path.
+fn files_stat_path(path: String, api_multiaddr: String, timeout_sec: u64) -> Result<IpfsStat> {
+    let cmd = Command::new(&["files", "stat"])
+        .option("with-local", true)
+        .arg(path);
+
+    let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
+    let get_u64 = |field: &str| result.get(field).and_then(|v| v.as_u64()).unwrap_or(0);
+    Ok(IpfsStat {
+        hash: get_json_str(&result, "Hash")?,
+        size: get_u64("Size"),
+        cumulative_size: get_u64("CumulativeSize"),
+        blocks: get_u64("Blocks"),
+        entry_type: get_json_str(&result, "Type")?,
+        local: result["Local"].as_bool().unwrap_or(false),
+        size_local: get_u64("SizeLocal"),
+    })
+}
+
+/// Get cumulative size and number of blocks of any DAG with `ipfs dag stat`.
+/// It walks the whole DAG, so blocks missing locally are fetched.
+#[marine]
+pub fn dag_stat(hash: String, api_multiaddr: String, timeout_sec: u64) -> IpfsStatResult {
+    log::info!("dag_stat called with hash {}", hash);
+
+    let result: Result<IpfsStat> = try {
+        let cmd = Command::new(&["dag", "stat"])
+            .option("progress", false)
+            .arg(hash.clone());
+        let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
+
+        // kubo 0.22 and later report stats per root along with the total size
+        let stats = result["DagStats"]
+            .as_array()
+            .and_then(|stats| stats.first())
+            .unwrap_or(&result);
+        let cumulative_size = result["TotalSize"]
+            .as_u64()
+            .or_else(|| stats["Size"].as_u64())
+            .ok_or(eyre::eyre!("Size field not found in response"))?;
+
+        IpfsStat {
+            hash,
+            size: 0,
+            cumulative_size,
+            blocks: stats["NumBlocks"].as_u64().unwrap_or(0),
+            entry_type: "dag".to_string(),
+            local: false,
+            size_local: 0,
+        }
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("dag_stat: {:?}", e)))
+        .into()
+}
+
+/// List links of the directory by provided hash, at most `max_entries` of them if it isn't 0.
+/// IPFS still lists the whole directory, output of mounted binaries can't be read partially,
+/// so `max_entries` only bounds the size of the result.
+/// Sizes and types of entries are resolved only if `resolve_type` is set,
+/// since that requires fetching a block of every entry. Otherwise size is 0 and type is
+/// `unknown`, except for raw blocks, which are always files.
+#[marine]
+pub fn ls(
+    hash: String,
+    resolve_type: bool,
+    max_entries: u64,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsLsResult {
+    log::info!("ls called with hash {}", hash);
+
+    let result: Result<(Vec<IpfsLsEntry>, bool)> = try {
+        let cmd = Command::new(&["ls"])
+            .option("size", resolve_type)
+            .option("resolve-type", resolve_type)
+            .arg(hash);
+
+        let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
+        let objects = result
+            .get("Objects")
+            .and_then(|objects| objects.as_array())
+            .ok_or(eyre::eyre!("Objects field not found in response"))?;
+
+        let mut entries = vec![];
+        let mut truncated = false;
+        for object in objects {
+            let links = object
+                .get("Links")
+                .and_then(|links| links.as_array())
+                .ok_or(eyre::eyre!("Links field not found in response"))?;
+            for link in links {
+                if max_entries != 0 && entries.len() as u64 >= max_entries {
+                    truncated = true;
+                    break;
+                }
+                entries.push(IpfsLsEntry {
+                    name: get_json_str(link, "Name")?,
+                    hash: get_json_str(link, "Hash")?,
+                    size: link.get("Size").and_then(|s| s.as_u64()).unwrap_or(0),
+                    entry_type: ls_entry_type(link.get("Type").and_then(|t| t.as_u64())),
+                });
+            }
+        }
+        (entries, truncated)
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("ls: {:?}", e)))
+        .into()
+}
+
+/// Map UnixFS data type reported by `ipfs ls` to entry type.
+fn ls_entry_type(data_type: Option<u64>) -> String {
+    match data_type {
+        Some(0) | Some(2) => "file",
+        Some(1) | Some(5) => "directory",
+        Some(4) => "symlink",
+        _ => "unknown",
+    }
+    .to_string()
+}
+
+/// Create MFS directory at `path`, along with missing parents if `parents` is set.
+#[marine]
+pub fn files_mkdir(
+    path: String,
+    parents: bool,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsResult {
+    log::info!("files_mkdir called with path {}", path);
+
+    let cmd = Command::new(&["files", "mkdir"])
+        .option("parents", parents)
+        .arg(path);
+
+    execute(cmd, api_multiaddr, timeout_sec).map(|_| ()).into()
+}
+
+/// Copy `source`, MFS or `/ipfs/<hash>` path, to MFS `dest` without copying the data.
+#[marine]
+pub fn files_cp(
+    source: String,
+    dest: String,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsResult {
+    log::info!("files_cp called with {} -> {}", source, dest);
+
+    let cmd = Command::new(&["files", "cp"]).arg(source).arg(dest);
+
+    execute(cmd, api_multiaddr, timeout_sec).map(|_| ()).into()
+}
+
+/// Write contents of the vault file at `file_path` to MFS file at `path`,
+/// creating it along with missing parents and replacing its previous contents.
+#[marine]
+pub fn files_write(
+    path: String,
+    file_path: String,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsResult {
+    log::info!(
+        "files_write called with path {} and file {}",
+        path,
+        file_path
+    );
+
+    let file_path = match confine_to_vault(&file_path) {
+        Ok(path) => path,
+        Err(e) => return Err(e).into(),
+    };
+    if !std::path::Path::new(&file_path).is_file() {
+        return Err(ErrorKind::NotFound.error(format!("file {} doesn't exist", file_path))).into();
+    }
+
+    let cmd = Command::new(&["files", "write"])
+        .option("create", true)
+        .option("parents", true)
+        .option("truncate", true)
+        .arg(path)
+        .input_file(file_path);
+
+    execute(cmd, api_multiaddr, timeout_sec).map(|_| ()).into()
+}
+
+/// Read at most `count` bytes of MFS file at `path` starting at `offset`.
+#[marine]
+pub fn files_read(
+    path: String,
+    offset: u64,
+    count: u64,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsCatResult {
+    log::info!("files_read called with path {}", path);
+
+    let cmd = Command::new(&["files", "read"])
+        .option("offset", offset)
+        .option("count", count)
+        .arg(path);
+
+    execute(cmd, api_multiaddr, timeout_sec).into()
+}
+
+/// List entries of MFS directory at `path`.
+#[marine]
+pub fn files_ls(path: String, api_multiaddr: String, timeout_sec: u64) -> IpfsLsResult {
+    log::info!("files_ls called with path {}", path);
 
-    log::info!("ipfs get args {:?}", cmd);
+    let result: Result<(Vec<IpfsLsEntry>, bool)> = try {
+        let cmd = Command::new(&["files", "ls"])
+            .option("long", true)
+            .arg(path);
+        let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
 
-    unwrap_mounted_binary_result(ipfs(cmd))
+        // kubo responds with null instead of an empty list for empty directories
+        let mut entries = vec![];
+        for entry in result["Entries"].as_array().into_iter().flatten() {
+            let entry_type = match entry["Type"].as_u64() {
+                Some(0) => "file",
+                Some(1) => "directory",
+                _ => "unknown",
+            };
+            entries.push(IpfsLsEntry {
+                name: get_json_str(entry, "Name")?,
+                hash: get_json_str(entry, "Hash")?,
+                size: entry["Size"].as_u64().unwrap_or(0),
+                entry_type: entry_type.to_string(),
+            });
+        }
+        (entries, false)
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("files_ls: {:?}", e)))
+        .into()
+}
+
+/// Remove MFS file or directory at `path`, directories are removed only if `recursive` is set.
+#[marine]
+pub fn files_rm(
+    path: String,
+    recursive: bool,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsResult {
+    log::info!("files_rm called with path {}", path);
+
+    let cmd = Command::new(&["files", "rm"])
+        .option("recursive", recursive)
+        .arg(path);
+
+    execute(cmd, api_multiaddr, timeout_sec).map(|_| ()).into()
+}
+
+/// Move MFS file or directory from `source` to `dest`.
+#[marine]
+pub fn files_mv(
+    source: String,
+    dest: String,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsResult {
+    log::info!("files_mv called with {} -> {}", source, dest);
+
+    let cmd = Command::new(&["files", "mv"]).arg(source).arg(dest);
+
+    execute(cmd, api_multiaddr, timeout_sec).map(|_| ()).into()
+}
+
+/// Get CID, size and type of MFS file or directory at `path`.
+#[marine]
+pub fn files_stat(path: String, api_multiaddr: String, timeout_sec: u64) -> IpfsStatResult {
+    log::info!("files_stat called with path {}", path);
+
+    files_stat_path(path, api_multiaddr, timeout_sec)
+        .map_err(|e| ErrorKind::of(&e).error(format!("files_stat: {:?}", e)))
+        .into()
+}
+
+/// Flush MFS `path` to the blockstore and return its CID.
+#[marine]
+pub fn files_flush(path: String, api_multiaddr: String, timeout_sec: u64) -> IpfsCidResult {
+    log::info!("files_flush called with path {}", path);
+
+    let result: Result<String> = try {
+        let cmd = Command::new(&["files", "flush"]).arg(path);
+        let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
+        get_json_str(&result, "Cid")?
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("files_flush: {:?}", e)))
+        .into()
+}
+
+/// Pin object by provided hash to local storage, so it is not removed by `ipfs repo gc`.
+#[marine]
+pub fn pin_add(
+    hash: String,
+    recursive: bool,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsResult {
+    log::info!("pin_add called with hash {}", hash);
+
+    let cmd = Command::new(&["pin", "add"])
+        .option("recursive", recursive)
+        .arg(hash);
+
+    execute(cmd, api_multiaddr, timeout_sec)
         .map(|output| {
-            log::info!("ipfs get output: {}", output);
+            log::info!("ipfs pin add output: {}", String::from_utf8_lossy(&output));
         })
         .into()
 }
 
+/// Remove pin of object by provided hash from local storage.
 #[marine]
-pub fn get_peer_id(api_multiaddr: String, timeout_sec: u64) -> IpfsGetPeerIdResult {
+pub fn pin_rm(
+    hash: String,
+    recursive: bool,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsResult {
+    log::info!("pin_rm called with hash {}", hash);
+
+    let cmd = Command::new(&["pin", "rm"])
+        .option("recursive", recursive)
+        .arg(hash);
+
+    execute(cmd, api_multiaddr, timeout_sec)
+        .map(|output| {
+            log::info!("ipfs pin rm output: {}", String::from_utf8_lossy(&output));
+        })
+        .into()
+}
+
+/// List pinned objects of the given type (all, recursive, direct or indirect).
+/// If hash is empty, all pins are listed.
+#[marine]
+pub fn pin_ls(
+    hash: String,
+    pin_type: String,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsPinLsResult {
+    log::info!("pin_ls called with hash {:?} and type {}", hash, pin_type);
+
+    let result: Result<Vec<IpfsPin>> = try {
+        let mut cmd = Command::new(&["pin", "ls"]).option("type", pin_type);
+        if !hash.is_empty() {
+            cmd = cmd.arg(hash);
+        }
+
+        let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
+        let keys = result
+            .get("Keys")
+            .ok_or(eyre::eyre!("Keys field not found in response"))?
+            .as_object()
+            .ok_or(eyre::eyre!("Keys value is not object"))?;
+
+        let mut pins = vec![];
+        for (hash, pin) in keys {
+            let pin_type = pin
+                .get("Type")
+                .ok_or(eyre::eyre!("Type field not found for pin {}", hash))?
+                .as_str()
+                .ok_or(eyre::eyre!("Type value is not string"))?
+                .to_string();
+            pins.push(IpfsPin {
+                hash: hash.clone(),
+                pin_type,
+            });
+        }
+        pins
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("pin_ls: {:?}", e)))
+        .into()
+}
+
+/// Verify that recursive pins are complete, return pins with missing or corrupted blocks.
+#[marine]
+pub fn pin_verify(api_multiaddr: String, timeout_sec: u64) -> IpfsPinVerifyResult {
+    log::info!("pin_verify called");
+
+    let result: Result<Vec<IpfsBadPin>> = try {
+        let cmd = Command::new(&["pin", "verify"]).option("encoding", "json");
+        let result = execute(cmd, api_multiaddr, timeout_sec)?;
+
+        // pin verify streams one JSON object per verified pin
+        let mut bad_pins = vec![];
+        for entry in serde_json::Deserializer::from_slice(&result).into_iter::<serde_json::Value>()
+        {
+            let entry = entry.wrap_err("ipfs response parsing failed")?;
+            let status = entry
+                .get("PinStatus")
+                .ok_or(eyre::eyre!("PinStatus field not found in response"))?;
+            if status.get("Ok").and_then(|ok| ok.as_bool()) == Some(true) {
+                continue;
+            }
+
+            let hash = entry
+                .get("Cid")
+                .and_then(json_cid)
+                .ok_or(eyre::eyre!("Cid field not found in response"))?;
+            let bad_nodes = status
+                .get("BadNodes")
+                .and_then(|nodes| nodes.as_array())
+                .map(|nodes| {
+                    nodes
+                        .iter()
+                        .filter_map(|node| node.get("Cid").and_then(json_cid))
+                        .collect()
+                })
+                .unwrap_or_default();
+            bad_pins.push(IpfsBadPin { hash, bad_nodes });
+        }
+        bad_pins
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("pin_verify: {:?}", e)))
+        .into()
+}
+
+/// Kubo encodes CIDs in JSON either as plain strings or as `{"/": "<cid>"}` links.
+fn json_cid(value: &serde_json::Value) -> Option<String> {
+    value
+        .as_str()
+        .or_else(|| value.get("/").and_then(|cid| cid.as_str()))
+        .map(|cid| cid.to_string())
+}
+
+/// Report size of the local repo, its configured maximum and number of stored objects.
+#[marine]
+pub fn repo_stat(api_multiaddr: String, timeout_sec: u64) -> IpfsRepoStatResult {
+    log::info!("repo_stat called");
+
+    let result: Result<IpfsRepoStat> = try {
+        let cmd = Command::new(&["repo", "stat"]);
+        let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
+
+        let get_u64 = |field: &str| {
+            result[field]
+                .as_u64()
+                .ok_or(eyre::eyre!("{} field not found in response", field))
+        };
+        IpfsRepoStat {
+            repo_size: get_u64("RepoSize")?,
+            storage_max: get_u64("StorageMax")?,
+            num_objects: get_u64("NumObjects")?,
+            repo_path: get_json_str(&result, "RepoPath")?,
+            version: get_json_str(&result, "Version")?,
+        }
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("repo_stat: {:?}", e)))
+        .into()
+}
+
+/// Remove unpinned blocks from the local repo and return how many were removed.
+#[marine]
+pub fn repo_gc(api_multiaddr: String, timeout_sec: u64) -> IpfsRepoGcResult {
+    log::info!("repo_gc called");
+
+    let result: Result<u64> = try {
+        let cmd = Command::new(&["repo", "gc"]).option("encoding", "json");
+        let output = execute(cmd, api_multiaddr, timeout_sec)?;
+
+        // gc streams one JSON object per removed block or per error
+        let mut removed = 0;
+        let mut errors = vec![];
+        for event in serde_json::Deserializer::from_slice(&output).into_iter::<serde_json::Value>()
+        {
+            let event = event.wrap_err("ipfs response parsing failed")?;
+            if let Some(error) = event["Error"].as_str().filter(|e| !e.is_empty()) {
+                errors.push(error.to_string());
+            } else if event.get("Key").is_some() {
+                removed += 1;
+            }
+        }
+
+        if !errors.is_empty() {
+            Err(ErrorKind::IpfsCliFailed.error(format!(
+                "gc failed after removing {} blocks: {}",
+                removed,
+                errors.join("; ")
+            )))?;
+        }
+        removed
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("repo_gc: {:?}", e)))
+        .into()
+}
+
+/// Check integrity of all blocks in the local repo, fails with `IntegrityCheckFailed`
+/// if some of them are corrupt.
+#[marine]
+pub fn repo_verify(api_multiaddr: String, timeout_sec: u64) -> IpfsRepoVerifyResult {
+    log::info!("repo_verify called");
+
+    let result: Result<u64> = try {
+        let cmd = Command::new(&["repo", "verify"]).option("encoding", "json");
+        let output = execute(cmd, api_multiaddr, timeout_sec)?;
+
+        // verify streams progress as the number of blocks checked so far
+        let mut verified = 0;
+        for event in serde_json::Deserializer::from_slice(&output).into_iter::<serde_json::Value>()
+        {
+            let event = event.wrap_err("ipfs response parsing failed")?;
+            if let Some(progress) = event["Progress"].as_u64() {
+                verified = std::cmp::max(verified, progress);
+            }
+            let message = event["Msg"].as_str().unwrap_or_default();
+            if message.contains("corrupt") {
+                Err(ErrorKind::IntegrityCheckFailed.error(message.to_string()))?;
+            }
+        }
+        verified
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("repo_verify: {:?}", e)))
+        .into()
+}
+
+/// Publish IPNS record pointing `key` to provided hash, return IPNS name and published value.
+/// Empty `lifetime` and `ttl` fall back to kubo defaults.
+#[marine]
+pub fn name_publish(
+    hash: String,
+    key: String,
+    lifetime: String,
+    ttl: String,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsNamePublishResult {
+    log::info!("name_publish called with hash {} and key {}", hash, key);
+
+    let result: Result<(String, String)> = try {
+        let mut cmd = Command::new(&["name", "publish"]).option("key", key);
+        if !lifetime.is_empty() {
+            cmd = cmd.option("lifetime", lifetime);
+        }
+        if !ttl.is_empty() {
+            cmd = cmd.option("ttl", ttl);
+        }
+
+        let result = execute_json(cmd.arg(hash), api_multiaddr, timeout_sec)?;
+        (
+            get_json_str(&result, "Name")?,
+            get_json_str(&result, "Value")?,
+        )
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("name_publish: {:?}", e)))
+        .into()
+}
+
+/// Resolve IPNS name to the path it points to.
+#[marine]
+pub fn name_resolve(
+    name: String,
+    recursive: bool,
+    nocache: bool,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsNameResolveResult {
+    log::info!("name_resolve called with name {}", name);
+
     let result: Result<String> = try {
-        let cmd = make_cmd_args(vec![String::from("id")], api_multiaddr, timeout_sec);
+        let cmd = Command::new(&["name", "resolve"])
+            .option("recursive", recursive)
+            .option("nocache", nocache)
+            .arg(name);
+
+        let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
+        get_json_str(&result, "Path")?
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("name_resolve: {:?}", e)))
+        .into()
+}
+
+/// Generate a new keypair with provided name. Empty `key_type` falls back to kubo default.
+#[marine]
+pub fn key_gen(
+    name: String,
+    key_type: String,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsKeyResult {
+    log::info!("key_gen called with name {}", name);
+
+    let result: Result<IpfsKey> = try {
+        let mut cmd = Command::new(&["key", "gen"]);
+        if !key_type.is_empty() {
+            cmd = cmd.option("type", key_type);
+        }
+
+        let result = execute_json(cmd.arg(name), api_multiaddr, timeout_sec)?;
+        parse_key(&result)?
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("key_gen: {:?}", e)))
+        .into()
+}
+
+/// List all local keypairs.
+#[marine]
+pub fn key_list(api_multiaddr: String, timeout_sec: u64) -> IpfsKeyListResult {
+    log::info!("key_list called");
+
+    let result: Result<Vec<IpfsKey>> = try {
+        let cmd = Command::new(&["key", "list"]).option("l", true);
 
-        let result = unwrap_mounted_binary_result(ipfs(cmd))?;
-        let result: serde_json::Value =
-            serde_json::from_str(&result).wrap_err("ipfs response parsing failed")?;
+        let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
+        parse_keys(&result)?
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("key_list: {:?}", e)))
+        .into()
+}
+
+/// Remove keypair by name, return removed keys.
+#[marine]
+pub fn key_rm(name: String, api_multiaddr: String, timeout_sec: u64) -> IpfsKeyListResult {
+    log::info!("key_rm called with name {}", name);
+
+    let result: Result<Vec<IpfsKey>> = try {
+        let cmd = Command::new(&["key", "rm"]).arg(name);
+
+        let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
+        parse_keys(&result)?
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("key_rm: {:?}", e)))
+        .into()
+}
+
+/// Rename keypair, return key with its new name.
+#[marine]
+pub fn key_rename(
+    old_name: String,
+    new_name: String,
+    force: bool,
+    api_multiaddr: String,
+    timeout_sec: u64,
+) -> IpfsKeyResult {
+    log::info!("key_rename called with {} -> {}", old_name, new_name);
+
+    let result: Result<IpfsKey> = try {
+        let cmd = Command::new(&["key", "rename"])
+            .option("force", force)
+            .arg(old_name)
+            .arg(new_name);
+
+        let result = execute_json(cmd, api_multiaddr, timeout_sec)?;
+        IpfsKey {
+            name: get_json_str(&result, "Now")?,
+            id: get_json_str(&result, "Id")?,
+        }
+    };
+
+    result
+        .map_err(|e| ErrorKind::of(&e).error(format!("key_rename: {:?}", e)))
+        .into()
+}
+
+fn get_json_str(value: &serde_json::Value, field: &str) -> Result<String> {
+    Ok(value
+        .get(field)
+        .ok_or(eyre::eyre!("{} field not found in response", field))?
+        .as_str()
+        .ok_or(eyre::eyre!("{} value is not string", field))?
+        .to_string())
+}
+
+fn parse_key(value: &serde_json::Value) -> Result<IpfsKey> {
+    Ok(IpfsKey {
+        name: get_json_str(value, "Name")?,
+        id: get_json_str(value, "Id")?,
+    })
+}
+
+fn parse_keys(value: &serde_json::Value) -> Result<Vec<IpfsKey>> {
+    value
+        .get("Keys")
+        .ok_or(eyre::eyre!("Keys field not found in response"))?
+        .as_array()
+        .ok_or(eyre::eyre!("Keys value is not array"))?
+        .iter()
+        .map(parse_key)
+        .collect()
+}
+
+#[marine]
+pub fn get_peer_id(api_multiaddr: String, timeout_sec: u64) -> IpfsGetPeerIdResult {
+    let result: Result<String> = try {
+        let result = execute_json(Command::new(&["id"]), api_multiaddr, timeout_sec)?;
         result
             .get("ID")
             .ok_or(eyre::eyre!("ID field not found in response"))?
@@ -188,23 +1210,6 @@ pub fn get_peer_id(api_multiaddr: String, timeout_sec: u64) -> IpfsGetPeerIdResu
     };
 
     result
-        .map_err(|e| eyre::eyre!("get_peer_id: {:?}", e))
+        .map_err(|e| ErrorKind::of(&e).error(format!("get_peer_id: {:?}", e)))
         .into()
 }
-
-#[marine]
-#[link(wasm_import_module = "host")]
-extern "C" {
-    /// Execute provided cmd as a parameters of ipfs cli, return result.
-    pub fn ipfs(cmd: Vec<String>) -> MountedBinaryResult;
-}
-
-fn inject_vault_host_path(path: String) -> String {
-    let vault = "/tmp/vault";
-    if let Some(stripped) = path.strip_prefix(&vault) {
-        let host_vault_path = std::env::var(vault).expect("vault must be mapped to /tmp/vault");
-        format!("/{}/{}", host_vault_path, stripped)
-    } else {
-        path
-    }
-}
@@ -16,8 +16,12 @@
 
 #![feature(try_blocks)]
 
+#[cfg(target_arch = "wasm32")]
+mod backend;
 #[cfg(target_arch = "wasm32")]
 mod effector;
+#[cfg(target_arch = "wasm32")]
+mod tar;
 
 /*
    _initialize function that calls __wasm_call_ctors is required to mitigade memory leak
@@ -0,0 +1,147 @@
+/*
+ * Copyright 2021 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Minimal unpacker for tar streams produced by kubo's `/api/v0/get`.
+//! Supports regular files, directories, PAX and GNU long names; other entries are skipped.
+
+use eyre::{Result, WrapErr};
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+const BLOCK_SIZE: usize = 512;
+
+/// Unpack `tar_path` to `output_path`. The top-level entry, named after the requested hash,
+/// is renamed to `output_path`, the same way `ipfs get -o` does.
+pub fn unpack(tar_path: &str, output_path: &str) -> Result<()> {
+    let file = File::open(tar_path).wrap_err(format!("failed to open {}", tar_path))?;
+    let mut reader = BufReader::new(file);
+    let mut long_name: Option<String> = None;
+
+    loop {
+        let mut header = [0u8; BLOCK_SIZE];
+        reader
+            .read_exact(&mut header)
+            .wrap_err("unexpected end of tar stream")?;
+        if header.iter().all(|b| *b == 0) {
+            return Ok(());
+        }
+
+        let size = parse_octal(&header[124..136])?;
+        let entry_type = header[156];
+        let name = long_name.take().unwrap_or_else(|| header_name(&header));
+
+        match entry_type {
+            b'x' => long_name = parse_pax_path(&read_data(&mut reader, size)?),
+            b'L' => {
+                let data = read_data(&mut reader, size)?;
+                long_name = Some(cstr(&data));
+            }
+            b'0' | 0 => {
+                let path = target_path(&name, output_path)?;
+                let mut file =
+                    File::create(&path).wrap_err(format!("failed to create {:?}", path))?;
+                let mut data = (&mut reader).take(size);
+                std::io::copy(&mut data, &mut file).wrap_err("failed to write file")?;
+                file.flush()?;
+                skip_padding(&mut reader, size)?;
+            }
+            b'5' => {
+                let path = target_path(&name, output_path)?;
+                std::fs::create_dir_all(&path).wrap_err(format!("failed to create {:?}", path))?;
+                skip(&mut reader, size)?;
+            }
+            other => {
+                log::warn!("skipping tar entry {} of type {}", name, other as char);
+                skip(&mut reader, size)?;
+            }
+        }
+    }
+}
+
+/// Replace the first component of `name` with `output_path` and reject entries escaping it.
+fn target_path(name: &str, output_path: &str) -> Result<PathBuf> {
+    let mut components = Path::new(name).components();
+    components.next();
+
+    let mut path = PathBuf::from(output_path);
+    for component in components {
+        match component {
+            Component::Normal(c) => path.push(c),
+            Component::CurDir => {}
+            _ => Err(eyre::eyre!(
+                "tar entry {} points outside of the target",
+                name
+            ))?,
+        }
+    }
+    Ok(path)
+}
+
+fn header_name(header: &[u8; BLOCK_SIZE]) -> String {
+    let name = cstr(&header[0..100]);
+    // ustar splits long names into prefix and name
+    if &header[257..262] == b"ustar" {
+        let prefix = cstr(&header[345..500]);
+        if !prefix.is_empty() {
+            return format!("{}/{}", prefix, name);
+        }
+    }
+    name
+}
+
+/// PAX records look like `<len> <key>=<value>\n`, only `path` is used.
+fn parse_pax_path(data: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(data).lines().find_map(|record| {
+        let (_, kv) = record.split_once(' ')?;
+        kv.strip_prefix("path=").map(|path| path.to_string())
+    })
+}
+
+fn cstr(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+fn parse_octal(bytes: &[u8]) -> Result<u64> {
+    let s = cstr(bytes);
+    let s = s.trim();
+    if s.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(s, 8).wrap_err(format!("invalid tar entry size {}", s))
+}
+
+fn read_data<R: Read>(reader: &mut R, size: u64) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    reader.take(size).read_to_end(&mut data)?;
+    if (data.len() as u64) < size {
+        Err(eyre::eyre!("unexpected end of tar stream"))?;
+    }
+    skip_padding(reader, size)?;
+    Ok(data)
+}
+
+fn skip<R: Read>(reader: &mut R, size: u64) -> Result<()> {
+    std::io::copy(&mut reader.take(size), &mut std::io::sink())?;
+    skip_padding(reader, size)
+}
+
+fn skip_padding<R: Read>(reader: &mut R, size: u64) -> Result<()> {
+    let padding = (BLOCK_SIZE as u64 - size % BLOCK_SIZE as u64) % BLOCK_SIZE as u64;
+    std::io::copy(&mut reader.take(padding), &mut std::io::sink())?;
+    Ok(())
+}
@@ -18,6 +18,17 @@
 mod tests {
     marine_rs_sdk_test::include_test_env!("/marine_test_env.rs");
 
+    fn call_parameters() -> marine_rs_sdk::CallParameters {
+        marine_rs_sdk::CallParameters {
+            init_peer_id: "init_peer_id".to_string(),
+            service_id: "service_id".to_string(),
+            service_creator_peer_id: "service_creator_peer_id".to_string(),
+            host_id: "host_id".to_string(),
+            particle_id: "particle_id".to_string(),
+            tetraplets: vec![],
+        }
+    }
+
     #[test]
     fn connect_failed() {
         let mut effector = marine_test_env::ipfs_error::ServiceInterface::new();
@@ -32,7 +43,157 @@ mod tests {
     #[test]
     fn put_result() {
         let mut effector = marine_test_env::ipfs_put::ServiceInterface::new();
-        let result = effector.put("tmp".to_string(), "api_multiaddr".to_string(), 1);
+        let options = marine_test_env::ipfs_put::IpfsPutOptions {
+            cid_version: 1,
+            hash: "sha2-256".to_string(),
+            chunker: "size-262144".to_string(),
+            raw_leaves: true,
+            trickle: false,
+            inline: false,
+            only_hash: false,
+        };
+        let result = effector.put_cp(
+            "/tmp/vault/particle_id/file".to_string(),
+            options.clone(),
+            "api_multiaddr".to_string(),
+            1,
+            call_parameters(),
+        );
         assert_eq!("hash", result.hash);
+
+        let result = effector.put_cp(
+            "/tmp/vault/particle_id/../../../etc/passwd".to_string(),
+            options,
+            "api_multiaddr".to_string(),
+            1,
+            call_parameters(),
+        );
+        assert!(!result.success);
+        assert_eq!("PermissionDenied", result.error_kind);
+    }
+
+    #[test]
+    fn put_through_symlinked_parent() {
+        let mut effector = marine_test_env::ipfs_put::ServiceInterface::new();
+        let options = marine_test_env::ipfs_put::IpfsPutOptions {
+            cid_version: 1,
+            hash: "sha2-256".to_string(),
+            chunker: "size-262144".to_string(),
+            raw_leaves: true,
+            trickle: false,
+            inline: false,
+            only_hash: false,
+        };
+        // tests/vault/particle_id/escape links to tests/, outside of the vault
+        let result = effector.put_cp(
+            "/tmp/vault/particle_id/escape/Config.toml".to_string(),
+            options,
+            "api_multiaddr".to_string(),
+            1,
+            call_parameters(),
+        );
+        assert!(!result.success);
+        assert_eq!("PermissionDenied", result.error_kind);
+    }
+
+    #[test]
+    fn flag_like_hash_rejected() {
+        let mut effector = marine_test_env::ipfs_put::ServiceInterface::new();
+        let result = effector.cat("--help".to_string(), 5, "api_multiaddr".to_string(), 1);
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+    }
+
+    #[test]
+    fn cat_result() {
+        let mut effector = marine_test_env::ipfs_put::ServiceInterface::new();
+        let result = effector.cat("hash".to_string(), 5, "api_multiaddr".to_string(), 1);
+        assert!(result.success);
+        assert_eq!(b"hash\n".to_vec(), result.contents);
+
+        let result = effector.cat("hash".to_string(), 4, "api_multiaddr".to_string(), 1);
+        assert!(!result.success);
+    }
+
+    #[test]
+    fn pin_ls_result() {
+        let mut effector = marine_test_env::ipfs_pin::ServiceInterface::new();
+        let result = effector.pin_ls(
+            "".to_string(),
+            "recursive".to_string(),
+            "api_multiaddr".to_string(),
+            1,
+        );
+        assert!(result.success);
+        assert_eq!(1, result.pins.len());
+        assert_eq!("QmPin", result.pins[0].hash);
+        assert_eq!("recursive", result.pins[0].pin_type);
+    }
+
+    #[test]
+    fn http_pin_ls_result() {
+        let mut effector = marine_test_env::ipfs_http::ServiceInterface::new();
+        let result = effector.pin_ls(
+            "".to_string(),
+            "recursive".to_string(),
+            "/ip4/127.0.0.1/tcp/5001".to_string(),
+            1,
+        );
+        assert!(result.success);
+        assert_eq!(1, result.pins.len());
+        assert_eq!("QmPin", result.pins[0].hash);
+    }
+
+    #[test]
+    fn http_timeout() {
+        let mut effector = marine_test_env::ipfs_http_timeout::ServiceInterface::new();
+        let result = effector.pin_ls(
+            "".to_string(),
+            "recursive".to_string(),
+            "/ip4/127.0.0.1/tcp/5001".to_string(),
+            1,
+        );
+        assert!(!result.success);
+        assert_eq!("Timeout", result.error_kind);
+    }
+
+    #[test]
+    fn cat_not_found() {
+        let mut effector = marine_test_env::ipfs_not_found::ServiceInterface::new();
+        let result = effector.cat("hash".to_string(), 5, "api_multiaddr".to_string(), 1);
+        assert!(!result.success);
+        assert_eq!("NotFound", result.error_kind);
+    }
+
+    #[test]
+    fn put_directory_not_found() {
+        let mut effector = marine_test_env::ipfs_put::ServiceInterface::new();
+        let result = effector.put_directory(
+            "/tmp/vault/missing_directory".to_string(),
+            false,
+            false,
+            vec![],
+            "api_multiaddr".to_string(),
+            1,
+        );
+        assert!(!result.success);
+        assert_eq!("NotFound", result.error_kind);
+    }
+
+    #[test]
+    fn ls_max_entries() {
+        let mut effector = marine_test_env::ipfs_ls::ServiceInterface::new();
+        let result = effector.ls("QmDir".to_string(), true, 0, "api_multiaddr".to_string(), 1);
+        assert!(result.success);
+        assert!(!result.truncated);
+        assert_eq!(2, result.entries.len());
+        assert_eq!("file", result.entries[0].entry_type);
+        assert_eq!("directory", result.entries[1].entry_type);
+
+        let result = effector.ls("QmDir".to_string(), true, 1, "api_multiaddr".to_string(), 1);
+        assert!(result.success);
+        assert!(result.truncated);
+        assert_eq!(1, result.entries.len());
+        assert_eq!("a", result.entries[0].name);
     }
 }
@@ -0,0 +1,252 @@
+/*
+ * Copyright 2021 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Reader of CAR (Content Addressable aRchive) v1 and v2 files, as produced by `ipfs dag export`.
+//! CARv1 is a dag-cbor header with roots followed by `<varint length><CID><block>` sections.
+//! CARv2 wraps CARv1 payload with a fixed header and an optional index, which is ignored.
+
+use crate::cid::{multihash_name, read_varint, Cid, MULTIHASH_IDENTITY, MULTIHASH_SHA2_256};
+
+use eyre::{Result, WrapErr};
+use sha2::{Digest, Sha256, Sha512};
+use std::convert::TryInto;
+use std::io::{Read, Take};
+
+const MULTIHASH_SHA2_512: u64 = 0x13;
+
+/// CARv2 starts with a CARv1-like header `{version: 2}` followed by 40 bytes of its own header.
+const CARV2_HEADER_SIZE: usize = 40;
+const CARV2_PRAGMA_SIZE: u64 = 11;
+/// Neither kubo nor other implementations produce blocks larger than a few MiB.
+const MAX_SECTION_SIZE: u64 = 8 * 1024 * 1024;
+const MAX_HEADER_SIZE: u64 = 1024 * 1024;
+const CBOR_MAX_DEPTH: usize = 16;
+const CBOR_CID_TAG: u64 = 42;
+
+pub struct CarReader<R: Read> {
+    reader: Take<R>,
+    pub version: u64,
+    pub roots: Vec<Cid>,
+}
+
+impl<R: Read> CarReader<R> {
+    /// Read CAR header, after that blocks can be read with `next_block`.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let (version, roots) = read_header(&mut reader)?;
+        match version {
+            1 => Ok(Self {
+                reader: reader.take(u64::MAX),
+                version,
+                roots,
+            }),
+            2 => {
+                let mut header = [0u8; CARV2_HEADER_SIZE];
+                reader
+                    .read_exact(&mut header)
+                    .wrap_err("unexpected end of CARv2 header")?;
+                // 16 bytes of characteristics bitfield, then little-endian offsets
+                let data_offset = u64::from_le_bytes(header[16..24].try_into()?);
+                let data_size = u64::from_le_bytes(header[24..32].try_into()?);
+
+                let header_end = CARV2_PRAGMA_SIZE + CARV2_HEADER_SIZE as u64;
+                if data_offset < header_end {
+                    Err(eyre::eyre!("invalid CARv2 data offset {}", data_offset))?;
+                }
+                std::io::copy(
+                    &mut (&mut reader).take(data_offset - header_end),
+                    &mut std::io::sink(),
+                )?;
+
+                let mut payload = reader.take(data_size);
+                let (inner_version, roots) = read_header(&mut payload)?;
+                if inner_version != 1 {
+                    Err(eyre::eyre!(
+                        "CARv2 payload must be CARv1, got version {}",
+                        inner_version
+                    ))?;
+                }
+
+                Ok(Self {
+                    reader: payload,
+                    version,
+                    roots,
+                })
+            }
+            _ => Err(eyre::eyre!("unsupported CAR version {}", version)),
+        }
+    }
+
+    /// Read next block, return `None` at the end of the archive.
+    pub fn next_block(&mut self) -> Result<Option<(Cid, Vec<u8>)>> {
+        let mut first = [0u8; 1];
+        if self.reader.read(&mut first)? == 0 {
+            return Ok(None);
+        }
+        let length = read_varint(&mut first.as_ref().chain(&mut self.reader))?;
+        // some writers pad CARv2 payload with zeros
+        if length == 0 {
+            return Ok(None);
+        }
+        if length > MAX_SECTION_SIZE {
+            Err(eyre::eyre!(
+                "CAR section of {} bytes exceeds maximum of {} bytes",
+                length,
+                MAX_SECTION_SIZE
+            ))?;
+        }
+
+        let mut section = vec![];
+        (&mut self.reader).take(length).read_to_end(&mut section)?;
+        if (section.len() as u64) < length {
+            Err(eyre::eyre!("unexpected end of CAR section"))?;
+        }
+
+        let mut data = section.as_slice();
+        let cid = Cid::read_bytes(&mut data).wrap_err("invalid block CID")?;
+        Ok(Some((cid, data.to_vec())))
+    }
+}
+
+/// Recompute multihash of the block and check that it matches the CID.
+/// Only sha2-256, sha2-512 and identity multihashes are supported.
+pub fn verify_block(cid: &Cid, data: &[u8]) -> Result<bool> {
+    let digest = match cid.hash.code() {
+        MULTIHASH_IDENTITY => return Ok(cid.hash.digest() == data),
+        MULTIHASH_SHA2_256 => Sha256::digest(data).to_vec(),
+        MULTIHASH_SHA2_512 => Sha512::digest(data).to_vec(),
+        other => Err(eyre::eyre!(
+            "unsupported multihash {} of block {}",
+            multihash_name(other),
+            cid
+        ))?,
+    };
+
+    // multihashes may be truncated to the declared size
+    let size = cid.hash.size() as usize;
+    Ok(size <= digest.len() && cid.hash.digest() == &digest[..size])
+}
+
+/// Read `<varint length><dag-cbor {version, roots}>` header.
+fn read_header<R: Read>(reader: &mut R) -> Result<(u64, Vec<Cid>)> {
+    let length = read_varint(reader).wrap_err("invalid CAR header length")?;
+    if length == 0 || length > MAX_HEADER_SIZE {
+        Err(eyre::eyre!("invalid CAR header length {}", length))?;
+    }
+
+    let mut bytes = vec![0u8; length as usize];
+    reader
+        .read_exact(&mut bytes)
+        .wrap_err("unexpected end of CAR header")?;
+    let mut data = bytes.as_slice();
+    let header = Cbor::read(&mut data, 0)?;
+
+    let entries = match header {
+        Cbor::Map(entries) => entries,
+        _ => Err(eyre::eyre!("CAR header must be a map"))?,
+    };
+
+    let mut version = None;
+    let mut roots = vec![];
+    for (key, value) in entries {
+        match (key, value) {
+            (Cbor::Text(key), Cbor::Uint(v)) if key == "version" => version = Some(v),
+            (Cbor::Text(key), Cbor::Array(items)) if key == "roots" => {
+                roots = items
+                    .into_iter()
+                    .map(Cbor::into_cid)
+                    .collect::<Result<_>>()?
+            }
+            _ => {}
+        }
+    }
+
+    let version = version.ok_or(eyre::eyre!("CAR header has no version"))?;
+    Ok((version, roots))
+}
+
+/// Subset of CBOR sufficient to decode CAR headers.
+enum Cbor {
+    Uint(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Map(Vec<(Cbor, Cbor)>),
+    Tag(u64, Box<Cbor>),
+    /// Negative integers, floats and simple values, which CAR headers don't use.
+    Other,
+}
+
+impl Cbor {
+    fn read(data: &mut &[u8], depth: usize) -> Result<Self> {
+        if depth > CBOR_MAX_DEPTH {
+            Err(eyre::eyre!("CBOR nesting is too deep"))?;
+        }
+
+        let initial = take(data, 1)?[0];
+        let major = initial >> 5;
+        let argument = match initial & 0x1f {
+            n @ 0..=23 => n as u64,
+            24 => take(data, 1)?[0] as u64,
+            25 => u16::from_be_bytes(take(data, 2)?.try_into()?) as u64,
+            26 => u32::from_be_bytes(take(data, 4)?.try_into()?) as u64,
+            27 => u64::from_be_bytes(take(data, 8)?.try_into()?),
+            other => Err(eyre::eyre!("unsupported CBOR additional info {}", other))?,
+        };
+
+        let value = match major {
+            0 => Cbor::Uint(argument),
+            2 => Cbor::Bytes(take(data, argument)?.to_vec()),
+            3 => Cbor::Text(
+                String::from_utf8(take(data, argument)?.to_vec()).wrap_err("invalid CBOR text")?,
+            ),
+            4 => Cbor::Array(
+                (0..argument)
+                    .map(|_| Cbor::read(data, depth + 1))
+                    .collect::<Result<_>>()?,
+            ),
+            5 => Cbor::Map(
+                (0..argument)
+                    .map(|_| Ok((Cbor::read(data, depth + 1)?, Cbor::read(data, depth + 1)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            6 => Cbor::Tag(argument, Box::new(Cbor::read(data, depth + 1)?)),
+            _ => Cbor::Other,
+        };
+
+        Ok(value)
+    }
+
+    /// dag-cbor encodes links as tag 42 over CID bytes prefixed with 0x00.
+    fn into_cid(self) -> Result<Cid> {
+        match self {
+            Cbor::Tag(CBOR_CID_TAG, value) => match *value {
+                Cbor::Bytes(bytes) if bytes.first() == Some(&0) => Cid::from_bytes(&bytes[1..]),
+                _ => Err(eyre::eyre!("invalid CID link in CAR header")),
+            },
+            _ => Err(eyre::eyre!("CAR roots must be CID links")),
+        }
+    }
+}
+
+fn take<'a>(data: &mut &'a [u8], size: u64) -> Result<&'a [u8]> {
+    if (data.len() as u64) < size {
+        Err(eyre::eyre!("unexpected end of CBOR data"))?;
+    }
+
+    let (head, tail) = data.split_at(size as usize);
+    *data = tail;
+    Ok(head)
+}
@@ -0,0 +1,406 @@
+/*
+ * Copyright 2021 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Parsing, inspection and conversion of CIDv0 and CIDv1 without calling IPFS.
+
+use eyre::{Result, WrapErr};
+use multiaddr::multihash::Multihash;
+use std::fmt;
+use std::io::Read;
+
+pub const CODEC_RAW: u64 = 0x55;
+pub const CODEC_DAG_PB: u64 = 0x70;
+pub const CODEC_DAG_CBOR: u64 = 0x71;
+pub const CODEC_LIBP2P_KEY: u64 = 0x72;
+pub const CODEC_DAG_JSON: u64 = 0x0129;
+
+pub const MULTIHASH_IDENTITY: u64 = 0x00;
+pub const MULTIHASH_SHA2_256: u64 = 0x12;
+
+const CODECS: [(u64, &str); 8] = [
+    (CODEC_RAW, "raw"),
+    (CODEC_DAG_PB, "dag-pb"),
+    (CODEC_DAG_CBOR, "dag-cbor"),
+    (CODEC_LIBP2P_KEY, "libp2p-key"),
+    (0x78, "git-raw"),
+    (CODEC_DAG_JSON, "dag-json"),
+    (0x0200, "json"),
+    (0x0201, "messagepack"),
+];
+
+const MULTIHASHES: [(u64, &str); 11] = [
+    (MULTIHASH_IDENTITY, "identity"),
+    (0x11, "sha1"),
+    (MULTIHASH_SHA2_256, "sha2-256"),
+    (0x13, "sha2-512"),
+    (0x14, "sha3-512"),
+    (0x15, "sha3-384"),
+    (0x16, "sha3-256"),
+    (0x17, "sha3-224"),
+    (0x1b, "keccak-256"),
+    (0x1e, "blake3"),
+    (0xb220, "blake2b-256"),
+];
+
+const CIDV0_LENGTH: usize = 46;
+const BASE36_ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+pub fn codec_name(code: u64) -> String {
+    CODECS
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or(format!("0x{:x}", code))
+}
+
+pub fn codec_from_name(name: &str) -> Result<u64> {
+    CODECS
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(code, _)| *code)
+        .ok_or(eyre::eyre!("unsupported codec: {}", name))
+}
+
+pub fn multihash_name(code: u64) -> String {
+    MULTIHASHES
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or(format!("0x{:x}", code))
+}
+
+pub fn multihash_from_name(name: &str) -> Result<u64> {
+    MULTIHASHES
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(code, _)| *code)
+        .ok_or(eyre::eyre!("unsupported multihash: {}", name))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Multibase {
+    Base16Lower,
+    Base16Upper,
+    Base32Lower,
+    Base32Upper,
+    Base36Lower,
+    Base36Upper,
+    Base58Btc,
+    Base64,
+    Base64Url,
+}
+
+const MULTIBASES: [(Multibase, char, &str); 9] = [
+    (Multibase::Base16Lower, 'f', "base16"),
+    (Multibase::Base16Upper, 'F', "base16upper"),
+    (Multibase::Base32Lower, 'b', "base32"),
+    (Multibase::Base32Upper, 'B', "base32upper"),
+    (Multibase::Base36Lower, 'k', "base36"),
+    (Multibase::Base36Upper, 'K', "base36upper"),
+    (Multibase::Base58Btc, 'z', "base58btc"),
+    (Multibase::Base64, 'm', "base64"),
+    (Multibase::Base64Url, 'u', "base64url"),
+];
+
+impl Multibase {
+    pub fn from_prefix(prefix: char) -> Result<Self> {
+        MULTIBASES
+            .iter()
+            .find(|(_, p, _)| *p == prefix)
+            .map(|(base, _, _)| *base)
+            .ok_or(eyre::eyre!("unsupported multibase prefix: {}", prefix))
+    }
+
+    pub fn from_name(name: &str) -> Result<Self> {
+        MULTIBASES
+            .iter()
+            .find(|(_, _, n)| *n == name)
+            .map(|(base, _, _)| *base)
+            .ok_or(eyre::eyre!("unsupported multibase: {}", name))
+    }
+
+    pub fn prefix(&self) -> char {
+        MULTIBASES.iter().find(|(b, _, _)| b == self).unwrap().1
+    }
+
+    pub fn name(&self) -> &'static str {
+        MULTIBASES.iter().find(|(b, _, _)| b == self).unwrap().2
+    }
+
+    /// Encode bytes without multibase prefix.
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        use data_encoding::{BASE32_NOPAD, BASE64, BASE64URL_NOPAD, HEXLOWER, HEXUPPER};
+
+        match self {
+            Multibase::Base16Lower => HEXLOWER.encode(bytes),
+            Multibase::Base16Upper => HEXUPPER.encode(bytes),
+            Multibase::Base32Lower => BASE32_NOPAD.encode(bytes).to_lowercase(),
+            Multibase::Base32Upper => BASE32_NOPAD.encode(bytes),
+            Multibase::Base36Lower => base36_encode(bytes),
+            Multibase::Base36Upper => base36_encode(bytes).to_uppercase(),
+            Multibase::Base58Btc => bs58::encode(bytes).into_string(),
+            Multibase::Base64 => BASE64.encode(bytes).trim_end_matches('=').to_string(),
+            Multibase::Base64Url => BASE64URL_NOPAD.encode(bytes),
+        }
+    }
+
+    /// Decode string without multibase prefix.
+    pub fn decode(&self, data: &str) -> Result<Vec<u8>> {
+        use data_encoding::{BASE32_NOPAD, BASE64URL_NOPAD, BASE64_NOPAD, HEXLOWER_PERMISSIVE};
+
+        let decoded = match self {
+            Multibase::Base16Lower | Multibase::Base16Upper => {
+                HEXLOWER_PERMISSIVE.decode(data.as_bytes())?
+            }
+            Multibase::Base32Lower | Multibase::Base32Upper => {
+                BASE32_NOPAD.decode(data.to_uppercase().as_bytes())?
+            }
+            Multibase::Base36Lower | Multibase::Base36Upper => base36_decode(&data.to_lowercase())?,
+            Multibase::Base58Btc => bs58::decode(data).into_vec()?,
+            Multibase::Base64 => BASE64_NOPAD.decode(data.trim_end_matches('=').as_bytes())?,
+            Multibase::Base64Url => BASE64URL_NOPAD.decode(data.as_bytes())?,
+        };
+
+        Ok(decoded)
+    }
+}
+
+/// Base36 is not supported by data-encoding, so it's done through big number division.
+fn base36_encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    let mut digits: Vec<u8> = vec![];
+    for byte in &bytes[zeros..] {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 36) as u8;
+            carry /= 36;
+        }
+        while carry > 0 {
+            digits.push((carry % 36) as u8);
+            carry /= 36;
+        }
+    }
+
+    std::iter::repeat('0')
+        .take(zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|d| BASE36_ALPHABET[*d as usize] as char),
+        )
+        .collect()
+}
+
+fn base36_decode(data: &str) -> Result<Vec<u8>> {
+    let zeros = data.chars().take_while(|c| *c == '0').count();
+    let mut bytes: Vec<u8> = vec![];
+    for c in data[zeros..].chars() {
+        let mut carry = BASE36_ALPHABET
+            .iter()
+            .position(|a| *a as char == c)
+            .ok_or(eyre::eyre!("invalid base36 character: {}", c))? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 36;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+
+    Ok(std::iter::repeat(0)
+        .take(zeros)
+        .chain(bytes.into_iter().rev())
+        .collect())
+}
+
+/// Read unsigned LEB128 varint as used by multiformats.
+pub fn read_varint<R: Read>(r: &mut R) -> Result<u64> {
+    let mut result = 0u64;
+    // multiformats varints are limited to 9 bytes (63 bits)
+    for i in 0..9 {
+        let mut byte = [0u8; 1];
+        r.read_exact(&mut byte)
+            .wrap_err("unexpected end of varint")?;
+        result |= ((byte[0] & 0x7f) as u64) << (i * 7);
+        if byte[0] & 0x80 == 0 {
+            if byte[0] == 0 && i > 0 {
+                Err(eyre::eyre!("varint is not minimally encoded"))?;
+            }
+            return Ok(result);
+        }
+    }
+
+    Err(eyre::eyre!("varint is too long"))
+}
+
+pub fn write_varint(mut value: u64, buf: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cid {
+    pub version: u64,
+    pub codec: u64,
+    pub hash: Multihash,
+}
+
+impl Cid {
+    pub fn new_v0(hash: Multihash) -> Result<Self> {
+        if hash.code() != MULTIHASH_SHA2_256 || hash.size() != 32 {
+            Err(eyre::eyre!("CIDv0 requires sha2-256 multihash"))?;
+        }
+
+        Ok(Self {
+            version: 0,
+            codec: CODEC_DAG_PB,
+            hash,
+        })
+    }
+
+    pub fn new_v1(codec: u64, hash: Multihash) -> Self {
+        Self {
+            version: 1,
+            codec,
+            hash,
+        }
+    }
+
+    /// Parse string CID, return it along with the multibase it was encoded with.
+    pub fn parse(cid: &str) -> Result<(Self, Multibase)> {
+        if cid.len() == CIDV0_LENGTH && cid.starts_with("Qm") {
+            let bytes = bs58::decode(cid).into_vec().wrap_err("invalid base58")?;
+            let hash = Multihash::from_bytes(&bytes).wrap_err("invalid multihash")?;
+            return Ok((Self::new_v0(hash)?, Multibase::Base58Btc));
+        }
+
+        let prefix = cid.chars().next().ok_or(eyre::eyre!("empty CID"))?;
+        let base = Multibase::from_prefix(prefix)?;
+        let bytes = base.decode(&cid[prefix.len_utf8()..])?;
+        let parsed = Self::from_bytes(&bytes)?;
+        if parsed.version == 0 {
+            Err(eyre::eyre!(
+                "CIDv0 must be base58btc encoded without prefix"
+            ))?;
+        }
+
+        Ok((parsed, base))
+    }
+
+    /// Read binary CID from the reader, leaving any trailing data unread.
+    pub fn read_bytes<R: Read>(r: &mut R) -> Result<Self> {
+        // binary CIDv0 is a bare sha2-256 multihash that starts with 0x12 0x20
+        let version = read_varint(r)?;
+        if version == MULTIHASH_SHA2_256 {
+            let mut rest = [0u8; 33];
+            r.read_exact(&mut rest)
+                .wrap_err("unexpected end of CIDv0")?;
+            let bytes: Vec<u8> = std::iter::once(MULTIHASH_SHA2_256 as u8)
+                .chain(rest.iter().copied())
+                .collect();
+            let hash = Multihash::from_bytes(&bytes).wrap_err("invalid multihash")?;
+            return Self::new_v0(hash);
+        }
+
+        if version != 1 {
+            Err(eyre::eyre!("unsupported CID version: {}", version))?;
+        }
+        let codec = read_varint(r)?;
+        let hash = Multihash::read(r).wrap_err("invalid multihash")?;
+
+        Ok(Self::new_v1(codec, hash))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = bytes;
+        let cid = Self::read_bytes(&mut reader)?;
+        if !reader.is_empty() {
+            Err(eyre::eyre!("unexpected {} trailing bytes", reader.len()))?;
+        }
+
+        Ok(cid)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        if self.version == 0 {
+            return self.hash.to_bytes();
+        }
+
+        let mut bytes = vec![];
+        write_varint(self.version, &mut bytes);
+        write_varint(self.codec, &mut bytes);
+        bytes.extend(self.hash.to_bytes());
+        bytes
+    }
+
+    pub fn into_v0(self) -> Result<Self> {
+        if self.codec != CODEC_DAG_PB {
+            Err(eyre::eyre!(
+                "only dag-pb CIDs can be converted to CIDv0, got {}",
+                codec_name(self.codec)
+            ))?;
+        }
+
+        Self::new_v0(self.hash)
+    }
+
+    pub fn into_v1(self) -> Self {
+        Self::new_v1(self.codec, self.hash)
+    }
+
+    pub fn to_string_of_base(&self, base: Multibase) -> Result<String> {
+        if self.version == 0 {
+            if base != Multibase::Base58Btc {
+                Err(eyre::eyre!("CIDv0 can only be encoded as base58btc"))?;
+            }
+            return Ok(bs58::encode(self.to_bytes()).into_string());
+        }
+
+        Ok(format!(
+            "{}{}",
+            base.prefix(),
+            base.encode(&self.to_bytes())
+        ))
+    }
+}
+
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let base = if self.version == 0 {
+            Multibase::Base58Btc
+        } else {
+            Multibase::Base32Lower
+        };
+        // encoding only fails for CIDv0 in non-base58 bases
+        write!(
+            f,
+            "{}",
+            self.to_string_of_base(base).map_err(|_| fmt::Error)?
+        )
+    }
+}
@@ -0,0 +1,265 @@
+/*
+ * Copyright 2021 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Persistent service configuration.
+//!
+//! Config is stored in `/storage`, which survives module restarts, and carries a schema version.
+//! Older layouts, including the unversioned `/tmp/multiaddr_config`, are migrated on load.
+//! New fields should have serde defaults, so that adding them doesn't require a migration.
+
+use eyre::WrapErr;
+use multiaddr::Multiaddr;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use types::{IpfsPutOptions, IpfsRetryPolicy};
+
+const CONFIG_FILE_PATH: &str = "/storage/config.toml";
+const LEGACY_CONFIG_FILE_PATH: &str = "/tmp/multiaddr_config";
+const CONFIG_VERSION: i64 = 1;
+pub const DEFAULT_TIMEOUT_SEC: u64 = 1u64;
+pub const DEFAULT_MAX_INLINE_SIZE: u64 = 1024 * 1024;
+/// Download size isn't limited by default, the limit costs an extra call to IPFS per download.
+pub const DEFAULT_MAX_DOWNLOAD_SIZE: u64 = 0;
+/// Classes of operations that can have their own timeout instead of the global one.
+pub const OPERATIONS: [&str; 10] = [
+    "connect", "put", "get", "dag", "id", "repo", "files", "pin", "name", "key",
+];
+
+#[derive(Deserialize, Serialize)]
+pub struct Config {
+    pub version: i64,
+    pub timeout: u64,
+    pub external_api_multiaddr: Option<Multiaddr>,
+    pub external_swarm_multiaddr: Option<Multiaddr>,
+    pub local_api_multiaddr: Option<Multiaddr>,
+    #[serde(default = "default_max_inline_size")]
+    pub max_inline_size: u64,
+    /// Maximum cumulative size of content downloaded to the vault, 0 means no limit.
+    #[serde(default = "default_max_download_size")]
+    pub max_download_size: u64,
+    /// Peers allowed to change config besides the service creator.
+    #[serde(default)]
+    pub admins: Vec<String>,
+    /// Service defaults of `ipfs add` parameters used by `put`.
+    #[serde(default)]
+    pub put_options: PutOptions,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Timeouts of operation classes from `OPERATIONS`, the global timeout is used for the rest.
+    #[serde(default)]
+    pub operation_timeouts: BTreeMap<String, u64>,
+}
+
+impl Config {
+    pub fn timeout_for(&self, operation: &str) -> u64 {
+        self.operation_timeouts
+            .get(operation)
+            .copied()
+            .unwrap_or(self.timeout)
+    }
+}
+
+/// Stored counterpart of `IpfsPutOptions`, defaults match the ones of kubo.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct PutOptions {
+    pub cid_version: u32,
+    pub hash: String,
+    pub chunker: String,
+    pub raw_leaves: bool,
+    pub trickle: bool,
+    pub inline: bool,
+    pub only_hash: bool,
+}
+
+impl Default for PutOptions {
+    fn default() -> Self {
+        PutOptions {
+            cid_version: 0,
+            hash: "sha2-256".to_string(),
+            chunker: "size-262144".to_string(),
+            raw_leaves: false,
+            trickle: false,
+            inline: false,
+            only_hash: false,
+        }
+    }
+}
+
+impl From<PutOptions> for IpfsPutOptions {
+    fn from(options: PutOptions) -> Self {
+        IpfsPutOptions {
+            cid_version: options.cid_version,
+            hash: options.hash,
+            chunker: options.chunker,
+            raw_leaves: options.raw_leaves,
+            trickle: options.trickle,
+            inline: options.inline,
+            only_hash: options.only_hash,
+        }
+    }
+}
+
+impl From<IpfsPutOptions> for PutOptions {
+    fn from(options: IpfsPutOptions) -> Self {
+        PutOptions {
+            cid_version: options.cid_version,
+            hash: options.hash,
+            chunker: options.chunker,
+            raw_leaves: options.raw_leaves,
+            trickle: options.trickle,
+            inline: options.inline,
+            only_hash: options.only_hash,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            version: CONFIG_VERSION,
+            timeout: DEFAULT_TIMEOUT_SEC,
+            external_api_multiaddr: None,
+            external_swarm_multiaddr: None,
+            local_api_multiaddr: None,
+            max_inline_size: DEFAULT_MAX_INLINE_SIZE,
+            max_download_size: DEFAULT_MAX_DOWNLOAD_SIZE,
+            admins: vec![],
+            put_options: PutOptions::default(),
+            retry: RetryPolicy::default(),
+            operation_timeouts: BTreeMap::new(),
+        }
+    }
+}
+
+/// Stored counterpart of `IpfsRetryPolicy`, retries are disabled by default.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub retryable_error_kinds: Vec<String>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            base_delay_ms: 500,
+            retryable_error_kinds: vec!["Timeout".to_string(), "IpfsUnavailable".to_string()],
+        }
+    }
+}
+
+impl From<RetryPolicy> for IpfsRetryPolicy {
+    fn from(policy: RetryPolicy) -> Self {
+        IpfsRetryPolicy {
+            max_attempts: policy.max_attempts,
+            base_delay_ms: policy.base_delay_ms,
+            retryable_error_kinds: policy.retryable_error_kinds,
+        }
+    }
+}
+
+impl From<IpfsRetryPolicy> for RetryPolicy {
+    fn from(policy: IpfsRetryPolicy) -> Self {
+        RetryPolicy {
+            max_attempts: policy.max_attempts,
+            base_delay_ms: policy.base_delay_ms,
+            retryable_error_kinds: policy.retryable_error_kinds,
+        }
+    }
+}
+
+fn default_max_inline_size() -> u64 {
+    DEFAULT_MAX_INLINE_SIZE
+}
+
+fn default_max_download_size() -> u64 {
+    DEFAULT_MAX_DOWNLOAD_SIZE
+}
+
+/// Write config to a temporary file first, so that a crash never leaves it half-written.
+pub fn write_config(config: Config) -> eyre::Result<()> {
+    let tmp_path = format!("{}.tmp", CONFIG_FILE_PATH);
+    let content = toml::to_string(&config).wrap_err("failed to serialize config")?;
+    fs::write(&tmp_path, content).wrap_err(format!("failed to write config {}", tmp_path))?;
+    fs::rename(&tmp_path, CONFIG_FILE_PATH)
+        .wrap_err(format!("failed to write config {}", CONFIG_FILE_PATH))?;
+    Ok(())
+}
+
+pub fn load_config() -> eyre::Result<Config> {
+    try_load_config().wrap_err("failed to load config, call reset_config to recover")
+}
+
+/// Load config, migrating it to the current version.
+/// If there is no config yet, the legacy one is migrated or the default one is created.
+pub fn try_load_config() -> eyre::Result<Config> {
+    let path = if Path::new(CONFIG_FILE_PATH).exists() {
+        CONFIG_FILE_PATH
+    } else if Path::new(LEGACY_CONFIG_FILE_PATH).exists() {
+        log::info!("migrating legacy config {}", LEGACY_CONFIG_FILE_PATH);
+        LEGACY_CONFIG_FILE_PATH
+    } else {
+        write_config(Config::default())?;
+        return Ok(Config::default());
+    };
+
+    let file_content =
+        fs::read_to_string(path).wrap_err(format!("failed to read config {}", path))?;
+    let config: toml::value::Table =
+        toml::from_str(&file_content).wrap_err(format!("failed to parse config {}", path))?;
+    let version = config
+        .get("version")
+        .and_then(|v| v.as_integer())
+        .unwrap_or(0);
+
+    if version > CONFIG_VERSION {
+        Err(eyre::eyre!(
+            "config version {} is newer than supported version {}",
+            version,
+            CONFIG_VERSION
+        ))?;
+    }
+
+    let config = migrate(config, version)?;
+    if version < CONFIG_VERSION {
+        log::info!(
+            "config migrated from version {} to {}",
+            version,
+            CONFIG_VERSION
+        );
+        write_config(config)?;
+        return try_load_config();
+    }
+
+    Ok(config)
+}
+
+/// Bring config of the given `version` to the current layout.
+fn migrate(mut config: toml::value::Table, version: i64) -> eyre::Result<Config> {
+    // version 0 is the unversioned layout of /tmp/multiaddr_config, it lacks only the version
+    if version < 1 {
+        config.insert("version".to_string(), toml::Value::Integer(1));
+    }
+
+    toml::Value::Table(config)
+        .try_into()
+        .wrap_err("config doesn't match the current layout")
+}
@@ -39,8 +39,20 @@ fn _initialize() {
     }
 }
 
+#[cfg(target_arch = "wasm32")]
+mod car;
+#[cfg(target_arch = "wasm32")]
+mod cid;
+#[cfg(target_arch = "wasm32")]
+mod config;
 #[cfg(target_arch = "wasm32")]
 mod pure;
+#[cfg(target_arch = "wasm32")]
+mod retry;
+#[cfg(target_arch = "wasm32")]
+mod unixfs;
+#[cfg(target_arch = "wasm32")]
+mod usage;
 
 #[cfg(target_arch = "wasm32")]
 pub fn main() {
@@ -16,20 +16,52 @@
 
 #![allow(improper_ctypes)]
 
-use types::{IpfsGetPeerIdResult, IpfsGetResult, IpfsMultiaddrResult, IpfsPutResult, IpfsResult};
+use types::{
+    ErrorKind, IpfsCarInfo, IpfsCarInfoResult, IpfsCarVerifyResult, IpfsCatResult, IpfsCidInfo,
+    IpfsCidInfoResult, IpfsCidResult, IpfsDagImportResult, IpfsGetFromAnyResult,
+    IpfsGetManifestResult, IpfsGetPeerIdResult, IpfsGetResult, IpfsGetStringResult,
+    IpfsKeyListResult, IpfsKeyResult, IpfsLsResult, IpfsManifestEntry, IpfsMultiaddrResult,
+    IpfsNamePublishResult, IpfsNameResolveResult, IpfsPeeringLsResult, IpfsPinLsResult,
+    IpfsPinVerifyResult, IpfsPutOptions, IpfsPutResult, IpfsRepoGcResult, IpfsRepoStatResult,
+    IpfsRepoVerifyResult, IpfsResult, IpfsRetryPolicy, IpfsSourceError, IpfsStatResult,
+    IpfsSwarmPeersResult, IpfsVaultUsageResult, PermissionDenied,
+};
 
 use marine_rs_sdk::marine;
 use marine_rs_sdk::module_manifest;
 use marine_rs_sdk::WasmLoggerBuilder;
 
+use crate::car::{verify_block, CarReader};
+use crate::cid::{
+    codec_name, multihash_from_name, multihash_name, Cid, Multibase, MULTIHASH_IDENTITY,
+    MULTIHASH_SHA2_256,
+};
+use crate::config::{load_config, try_load_config, write_config, Config, RetryPolicy, OPERATIONS};
+use crate::retry::{with_retry, MAX_TOTAL_DELAY_MS};
+use crate::unixfs::{file_cid, parse_chunker, ImportParams};
+use crate::usage::{self, record_vault_write};
+
 use eyre::WrapErr;
 use multiaddr::{multihash::Multihash, Multiaddr, Protocol};
-use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
+use std::io::BufReader;
+use std::path::{Component, Path};
 use std::str::FromStr;
 
-const CONFIG_FILE_PATH: &str = "/tmp/multiaddr_config";
-const DEFAULT_TIMEOUT_SEC: u64 = 1u64;
+const INLINE_PUT_FILE_NAME: &str = "inline_put";
+const PIN_TYPES: [&str; 4] = ["all", "recursive", "direct", "indirect"];
+const KEY_TYPES: [&str; 2] = ["ed25519", "rsa"];
+const DEFAULT_IPNS_KEY: &str = "self";
+const DAG_CODECS: [&str; 4] = ["dag-json", "dag-cbor", "dag-pb", "raw"];
+const DEFAULT_DAG_INPUT_CODEC: &str = "dag-json";
+const DEFAULT_DAG_STORE_CODEC: &str = "dag-cbor";
+const DEFAULT_DAG_OUTPUT_CODEC: &str = "dag-json";
+const DEFAULT_DAG_HASH: &str = "sha2-256";
+const MAX_RETRY_ATTEMPTS: u32 = 10;
+const DEFAULT_LS_MAX_ENTRIES: u64 = 1000;
+/// MFS directory holding roots of all services, every service works under `<prefix>/<service id>`.
+const MFS_ROOT_PREFIX: &str = "/aqua-ipfs";
 module_manifest!();
 
 pub fn main() {
@@ -37,64 +69,114 @@ pub fn main() {
         .with_log_level(log::LevelFilter::Info)
         .build()
         .unwrap();
-    reset_config();
-}
 
-#[derive(Deserialize, Serialize)]
-pub struct Config {
-    pub timeout: u64,
-    pub external_api_multiaddr: Option<Multiaddr>,
-    pub external_swarm_multiaddr: Option<Multiaddr>,
-    pub local_api_multiaddr: Option<Multiaddr>,
+    // config persists between restarts, so it's only migrated here
+    if let Err(e) = try_load_config() {
+        log::error!("failed to load config: {:?}", e);
+    }
 }
 
-fn save_external_api_multiaddr(multiaddr: Multiaddr) {
-    let mut config = load_config();
+fn save_external_api_multiaddr(multiaddr: Multiaddr) -> eyre::Result<()> {
+    let mut config = load_config()?;
     config.external_api_multiaddr = Some(multiaddr);
-    write_config(config);
+    write_config(config)
 }
 
 fn load_external_api_multiaddr() -> eyre::Result<Multiaddr> {
-    load_config()
+    load_config()?
         .external_api_multiaddr
-        .ok_or(eyre::eyre!("external api multiaddr is not set"))
+        .ok_or(ErrorKind::NotConfigured.error("external api multiaddr is not set"))
 }
 
-fn save_local_api_multiaddr(multiaddr: Multiaddr) {
-    let mut config = load_config();
+fn save_local_api_multiaddr(multiaddr: Multiaddr) -> eyre::Result<()> {
+    let mut config = load_config()?;
     config.local_api_multiaddr = Some(multiaddr);
-    write_config(config);
+    write_config(config)
+}
+
+/// Config for getters that can't report errors, defaults are returned if it fails to load.
+fn load_config_or_default() -> Config {
+    load_config().unwrap_or_else(|e| {
+        log::error!("{:?}", e);
+        Config::default()
+    })
 }
 
 fn load_local_api_multiaddr() -> eyre::Result<Multiaddr> {
-    load_config()
+    load_config()?
         .local_api_multiaddr
-        .ok_or(eyre::eyre!("local api multiaddr is not set"))
+        .ok_or(ErrorKind::NotConfigured.error("local api multiaddr is not set"))
+}
+
+/// Multiaddrs can only be set once, but setting the same value again is allowed,
+/// so that `on_start` script can be re-run after restart.
+fn is_same_multiaddr(current: &Multiaddr, given: &str) -> bool {
+    let mut without_peer_id = current.clone();
+    if let Some(Protocol::P2p(_)) = without_peer_id.iter().last() {
+        without_peer_id.pop();
+    }
+
+    Multiaddr::from_str(given)
+        .map(|given| &given == current || given == without_peer_id)
+        .unwrap_or(false)
 }
 
-pub fn write_config(config: Config) {
-    fs::write(CONFIG_FILE_PATH, toml::to_string(&config).unwrap()).unwrap();
+fn get_particle_vault_path() -> String {
+    let particle_id = marine_rs_sdk::get_call_parameters().particle_id;
+    format!("/tmp/vault/{}", particle_id)
 }
 
-pub fn load_config() -> Config {
-    let file_content = fs::read_to_string(CONFIG_FILE_PATH).unwrap();
-    let config: Config = toml::from_str(&file_content).unwrap();
-    config
+/// Turn error returned by effector back into an error of the same kind.
+fn effector_error(error: String, error_kind: &str) -> eyre::Report {
+    ErrorKind::parse(error_kind).error(error)
 }
 
-pub(crate) fn reset_config() {
-    write_config(Config {
-        timeout: DEFAULT_TIMEOUT_SEC,
-        external_api_multiaddr: None,
-        external_swarm_multiaddr: None,
-        local_api_multiaddr: None,
-    });
+fn check_service_creator(action: &str) -> eyre::Result<()> {
+    let call_parameters = marine_rs_sdk::get_call_parameters();
+    if call_parameters.init_peer_id != call_parameters.service_creator_peer_id {
+        Err(PermissionDenied {
+            peer_id: call_parameters.init_peer_id,
+            action: action.to_string(),
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Service creator is always an admin, other admins are stored in config.
+fn get_admins(config: &Config) -> Vec<String> {
+    let creator = marine_rs_sdk::get_call_parameters().service_creator_peer_id;
+    std::iter::once(creator.clone())
+        .chain(config.admins.iter().filter(|a| **a != creator).cloned())
+        .collect()
+}
+
+fn check_admin(action: &str) -> eyre::Result<()> {
+    let init_peer_id = marine_rs_sdk::get_call_parameters().init_peer_id;
+    if !get_admins(&load_config()?).contains(&init_peer_id) {
+        Err(PermissionDenied {
+            peer_id: init_peer_id,
+            action: action.to_string(),
+        })?;
+    }
+
+    Ok(())
+}
+
+fn validate_peer_id(peer_id: &str) -> eyre::Result<()> {
+    let invalid = || ErrorKind::InvalidArgument.error(format!("invalid peer id {}", peer_id));
+    let bytes = bs58::decode(peer_id).into_vec().map_err(|_| invalid())?;
+    Multihash::from_bytes(&bytes).map_err(|_| invalid())?;
+    Ok(())
 }
 
 pub fn get_peer_id(api_multiaddr: String, timeout: u64) -> eyre::Result<Protocol<'static>> {
     let peer_id_result = ipfs_get_peer_id(api_multiaddr, timeout);
     if !peer_id_result.success {
-        Err(eyre::eyre!(peer_id_result.error.clone()))?;
+        Err(effector_error(
+            peer_id_result.error.clone(),
+            &peer_id_result.error_kind,
+        ))?;
     }
 
     Ok(Protocol::P2p(
@@ -110,281 +192,2115 @@ pub fn get_peer_id(api_multiaddr: String, timeout: u64) -> eyre::Result<Protocol
 #[marine]
 pub fn connect(multiaddr: String) -> IpfsResult {
     if Multiaddr::from_str(&multiaddr).is_err() {
-        return Err(eyre::eyre!("invalid multiaddr: {}", multiaddr)).into();
+        return Err(ErrorKind::InvalidMultiaddr.error(format!("invalid multiaddr: {}", multiaddr)))
+            .into();
     }
 
-    let timeout = load_config().timeout;
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
     let local_maddr = load_local_api_multiaddr().map(|m| m.to_string());
 
     if local_maddr.is_ok() {
-        ipfs_connect(multiaddr, local_maddr.unwrap(), timeout)
+        let local_maddr = local_maddr.unwrap();
+        with_retry(&config.retry, |_| {
+            ipfs_connect(
+                multiaddr.clone(),
+                local_maddr.clone(),
+                config.timeout_for("connect"),
+            )
+        })
     } else {
         local_maddr.map(drop).into()
     }
 }
 
+/// List peers the local IPFS node is connected to.
 #[marine]
-pub fn put(file_path: String) -> IpfsPutResult {
-    log::info!("put called with {:?}", file_path);
-    let timeout = load_config().timeout;
-    let local_maddr = load_local_api_multiaddr().map(|m| m.to_string());
-    if local_maddr.is_ok() {
-        ipfs_put(file_path, local_maddr.unwrap(), timeout)
-    } else {
-        local_maddr.into()
+pub fn swarm_peers() -> IpfsSwarmPeersResult {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_swarm_peers(local_maddr.to_string(), config.timeout_for("connect")),
+        Err(e) => Err(e).into(),
     }
 }
 
 #[marine]
-pub fn dag_put(file_path: String) -> IpfsPutResult {
-    log::info!("dag_put called with {:?}", file_path);
-    let timeout = load_config().timeout;
-    let local_maddr = load_local_api_multiaddr().map(|m| m.to_string());
-    if local_maddr.is_ok() {
-        ipfs_dag_put(file_path, local_maddr.unwrap(), timeout)
-    } else {
-        local_maddr.into()
+pub fn swarm_disconnect(multiaddr: String) -> IpfsResult {
+    log::info!("swarm_disconnect called with multiaddr: {}", multiaddr);
+    if let Err(e) = check_admin("disconnect peers") {
+        return Err(e).into();
+    }
+
+    if Multiaddr::from_str(&multiaddr).is_err() {
+        return Err(ErrorKind::InvalidMultiaddr.error(format!("invalid multiaddr: {}", multiaddr)))
+            .into();
+    }
+
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_swarm_disconnect(
+            multiaddr,
+            local_maddr.to_string(),
+            config.timeout_for("connect"),
+        ),
+        Err(e) => Err(e).into(),
     }
 }
 
+/// Keep the local IPFS node permanently connected to the peer at `multiaddr`,
+/// which must end with `/p2p/<peer id>`.
 #[marine]
-pub fn get(hash: String) -> IpfsGetResult {
-    let local_maddr = load_local_api_multiaddr().map(|m| m.to_string());
-    if local_maddr.is_ok() {
-        get_from(hash, local_maddr.unwrap())
-    } else {
-        local_maddr.into()
+pub fn swarm_peering_add(multiaddr: String) -> IpfsResult {
+    log::info!("swarm_peering_add called with multiaddr: {}", multiaddr);
+    if let Err(e) = check_admin("add peering") {
+        return Err(e).into();
+    }
+
+    match Multiaddr::from_str(&multiaddr) {
+        Ok(maddr) if matches!(maddr.iter().last(), Some(Protocol::P2p(_))) => {}
+        Ok(_) => {
+            return Err(ErrorKind::InvalidMultiaddr.error(format!(
+                "multiaddr {} must end with /p2p/<peer id>",
+                multiaddr
+            )))
+            .into()
+        }
+        Err(_) => {
+            return Err(
+                ErrorKind::InvalidMultiaddr.error(format!("invalid multiaddr: {}", multiaddr))
+            )
+            .into()
+        }
+    }
+
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_swarm_peering_add(
+            multiaddr,
+            local_maddr.to_string(),
+            config.timeout_for("connect"),
+        ),
+        Err(e) => Err(e).into(),
     }
 }
 
 #[marine]
-pub fn dag_get(hash: String) -> IpfsGetResult {
-    let local_maddr = load_local_api_multiaddr().map(|m| m.to_string());
-    if local_maddr.is_ok() {
-        dag_get_from(hash, local_maddr.unwrap())
-    } else {
-        local_maddr.into()
+pub fn swarm_peering_rm(peer_id: String) -> IpfsResult {
+    log::info!("swarm_peering_rm called with peer id: {}", peer_id);
+    if let Err(e) = check_admin("remove peering") {
+        return Err(e).into();
+    }
+    if let Err(e) = validate_peer_id(&peer_id) {
+        return Err(e).into();
+    }
+
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_swarm_peering_rm(
+            peer_id,
+            local_maddr.to_string(),
+            config.timeout_for("connect"),
+        ),
+        Err(e) => Err(e).into(),
     }
 }
 
 #[marine]
-pub fn get_from(hash: String, external_multiaddr: String) -> IpfsGetResult {
-    log::info!("get from called with hash: {}", hash);
-    let config = load_config();
-    let timeout = config.timeout;
+pub fn swarm_peering_ls() -> IpfsPeeringLsResult {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => {
+            ipfs_swarm_peering_ls(local_maddr.to_string(), config.timeout_for("connect"))
+        }
+        Err(e) => Err(e).into(),
+    }
+}
 
-    let particle_id = marine_rs_sdk::get_call_parameters().particle_id;
-    if Multiaddr::from_str(&external_multiaddr).is_err() {
-        return Err(eyre::eyre!("invalid multiaddr: {}", external_multiaddr)).into();
+#[marine]
+pub fn put(file_path: String) -> IpfsPutResult {
+    log::info!("put called with {:?}", file_path);
+    match load_config() {
+        Ok(config) => put_with_options(file_path, config.put_options.into()),
+        Err(e) => Err(e).into(),
+    }
+}
+
+/// Put file to IPFS with the given `ipfs add` parameters instead of the service defaults.
+#[marine]
+pub fn put_with_options(file_path: String, options: IpfsPutOptions) -> IpfsPutResult {
+    log::info!(
+        "put_with_options called with {:?} and {:?}",
+        file_path,
+        options
+    );
+    if let Err(e) = validate_put_options(&options) {
+        return Err(e).into();
     }
 
-    let particle_vault_path = format!("/tmp/vault/{}", particle_id);
-    println!("particle_vault_path {}", particle_vault_path);
-    let path = format!("{}/{}", particle_vault_path, hash);
-    let get_result = ipfs_get(hash, path.clone(), external_multiaddr, timeout);
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => with_retry(&config.retry, |_| {
+            ipfs_put(
+                file_path.clone(),
+                options.clone(),
+                local_maddr.to_string(),
+                config.timeout_for("put"),
+            )
+        }),
+        Err(e) => Err(e).into(),
+    }
+}
 
-    if get_result.success {
-        Ok(path).into()
-    } else {
-        Err(eyre::eyre!(get_result.error)).into()
+fn validate_put_options(options: &IpfsPutOptions) -> eyre::Result<()> {
+    if options.cid_version > 1 {
+        Err(ErrorKind::InvalidArgument.error(format!(
+            "invalid cid version {}, expected 0 or 1",
+            options.cid_version
+        )))?;
+    }
+    // kubo silently switches to CIDv1 otherwise, which breaks reproducibility
+    if options.cid_version == 0 && options.hash != "sha2-256" {
+        Err(ErrorKind::InvalidArgument
+            .error(format!("hash {} requires cid version 1", options.hash)))?;
     }
+    if options.hash.is_empty() || options.chunker.is_empty() {
+        Err(ErrorKind::InvalidArgument.error("hash and chunker must be set"))?;
+    }
+
+    Ok(())
 }
 
+/// Put string contents to IPFS without creating a file beforehand.
 #[marine]
-pub fn dag_get_from(hash: String, external_multiaddr: String) -> IpfsGetResult {
-    log::info!("get from called with hash: {}", hash);
-    let config = load_config();
-    let timeout = config.timeout;
+pub fn put_string(contents: String) -> IpfsPutResult {
+    put_bytes(contents.into_bytes())
+}
 
-    let particle_id = marine_rs_sdk::get_call_parameters().particle_id;
-    if Multiaddr::from_str(&external_multiaddr).is_err() {
-        return Err(eyre::eyre!("invalid multiaddr: {}", external_multiaddr)).into();
+/// Put contents to IPFS without creating a file beforehand.
+/// Contents are written to the particle vault and removed after the put.
+#[marine]
+pub fn put_bytes(contents: Vec<u8>) -> IpfsPutResult {
+    log::info!("put_bytes called with {} bytes", contents.len());
+    let path = format!("{}/{}", get_particle_vault_path(), INLINE_PUT_FILE_NAME);
+    if let Err(e) = fs::write(&path, contents) {
+        return Err(eyre::eyre!("failed to write contents to {}: {}", path, e)).into();
     }
 
-    let particle_vault_path = format!("/tmp/vault/{}", particle_id);
-    println!("particle_vault_path {}", particle_vault_path);
-    let path = format!("{}/{}", particle_vault_path, hash);
-    let get_result = ipfs_dag_get(hash, path.clone(), external_multiaddr, timeout);
+    let result = put(path.clone());
+    if let Err(e) = fs::remove_file(&path) {
+        log::warn!("failed to remove {}: {}", path, e);
+    }
+    result
+}
 
-    if get_result.success {
-        Ok(path).into()
-    } else {
-        Err(eyre::eyre!(get_result.error)).into()
+/// Put directory to IPFS recursively and return hash of its root.
+/// If `wrap_with_directory` is set, the directory is wrapped into another one, keeping its name.
+/// Hidden files are skipped unless `hidden` is set, `ignore` contains patterns of names to skip.
+#[marine]
+pub fn put_directory(
+    path: String,
+    wrap_with_directory: bool,
+    hidden: bool,
+    ignore: Vec<String>,
+) -> IpfsPutResult {
+    log::info!("put_directory called with {:?}", path);
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => with_retry(&config.retry, |_| {
+            ipfs_put_directory(
+                path.clone(),
+                wrap_with_directory,
+                hidden,
+                ignore.clone(),
+                local_maddr.to_string(),
+                config.timeout_for("put"),
+            )
+        }),
+        Err(e) => Err(e).into(),
     }
 }
 
+/// DAG put dag-json file to IPFS, the node is stored as dag-cbor.
 #[marine]
-pub fn get_external_api_multiaddr() -> IpfsMultiaddrResult {
-    load_external_api_multiaddr().map(|m| m.to_string()).into()
+pub fn dag_put(file_path: String) -> IpfsPutResult {
+    dag_put_with_options(
+        file_path,
+        DEFAULT_DAG_INPUT_CODEC.to_string(),
+        DEFAULT_DAG_STORE_CODEC.to_string(),
+        DEFAULT_DAG_HASH.to_string(),
+    )
 }
 
+/// DAG put file encoded with `input_codec` to IPFS, storing the node with `store_codec`
+/// and `hash` function. Codecs are dag-json, dag-cbor, dag-pb or raw.
 #[marine]
-pub fn set_external_api_multiaddr(multiaddr: String) -> IpfsResult {
-    if load_external_api_multiaddr().is_ok() {
-        return eyre::Result::<()>::Err(eyre::eyre!("external api multiaddr can only be set once"))
-            .into();
+pub fn dag_put_with_options(
+    file_path: String,
+    input_codec: String,
+    store_codec: String,
+    hash: String,
+) -> IpfsPutResult {
+    log::info!(
+        "dag_put_with_options called with {:?}, input codec {}, store codec {}, hash {}",
+        file_path,
+        input_codec,
+        store_codec,
+        hash
+    );
+    let result: eyre::Result<()> = try {
+        validate_dag_codec(&input_codec)?;
+        validate_dag_codec(&store_codec)?;
+        multihash_from_name(&hash).map_err(|e| ErrorKind::InvalidArgument.error(e.to_string()))?;
+    };
+    if let Err(e) = result {
+        return Err(e).into();
     }
 
-    let call_parameters = marine_rs_sdk::get_call_parameters();
-    if call_parameters.init_peer_id != call_parameters.service_creator_peer_id {
-        return eyre::Result::<()>::Err(eyre::eyre!(
-            "only service creator can set external api multiaddr"
-        ))
-        .into();
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => with_retry(&config.retry, |_| {
+            ipfs_dag_put(
+                file_path.clone(),
+                input_codec.clone(),
+                store_codec.clone(),
+                hash.clone(),
+                local_maddr.to_string(),
+                config.timeout_for("dag"),
+            )
+        }),
+        Err(e) => Err(e).into(),
+    }
+}
+
+fn validate_dag_codec(codec: &str) -> eyre::Result<()> {
+    if !DAG_CODECS.contains(&codec) {
+        Err(ErrorKind::InvalidArgument.error(format!(
+            "unsupported dag codec {}, expected one of {}",
+            codec,
+            DAG_CODECS.join(", ")
+        )))?;
+    }
+
+    Ok(())
+}
+
+#[marine]
+pub fn get(hash: String) -> IpfsGetResult {
+    let local_maddr = load_local_api_multiaddr().map(|m| m.to_string());
+    if local_maddr.is_ok() {
+        get_from(hash, local_maddr.unwrap())
+    } else {
+        local_maddr.into()
     }
+}
 
-    let config = load_config();
-    let timeout = config.timeout;
+/// Read contents by hash from local IPFS without saving them to the vault.
+/// Fails if contents are larger than the configured maximum inline size.
+#[marine]
+pub fn cat(hash: String) -> IpfsCatResult {
+    log::info!("cat called with hash: {}", hash);
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_cat(
+            hash,
+            config.max_inline_size,
+            local_maddr.to_string(),
+            config.timeout_for("get"),
+        ),
+        Err(e) => Err(e).into(),
+    }
+}
 
+/// Read `length` bytes of the file starting at `offset` from local IPFS without saving them
+/// to the vault, e.g. to preview a large file. Fails if `length` is larger than
+/// the configured maximum inline size, use `get_range` for larger slices.
+#[marine]
+pub fn cat_range(hash: String, offset: u64, length: u64) -> IpfsCatResult {
+    log::info!(
+        "cat_range called with hash: {}, offset: {}, length: {}",
+        hash,
+        offset,
+        length
+    );
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
     let result: eyre::Result<()> = try {
-        let mut multiaddr = Multiaddr::from_str(&multiaddr)
-            .wrap_err(format!("invalid multiaddr: {}", multiaddr))?;
-        let local_maddr = load_local_api_multiaddr()?.to_string();
-        let mut passed_peer_id = None;
-        match multiaddr.iter().count() {
-            3 => {
-                passed_peer_id = multiaddr.pop();
-            }
-            2 => {}
-            n => Err(eyre::eyre!(
-                "multiaddr should contain 2 or 3 components, {} given",
-                n
-            ))?,
+        validate_range(&hash, length)?;
+        if length > config.max_inline_size {
+            Err(ErrorKind::InvalidArgument.error(format!(
+                "length {} exceeds maximum inline size of {} bytes",
+                length, config.max_inline_size
+            )))?;
         }
+    };
+    if let Err(e) = result {
+        return Err(e).into();
+    }
 
-        let peer_id = get_peer_id(local_maddr, timeout)?;
-        if passed_peer_id.is_some() && passed_peer_id != Some(peer_id.clone()) {
-            Err(eyre::eyre!(
-                "given peer id is different from node peer_id: given {}, actual {}",
-                passed_peer_id.unwrap().to_string(),
-                peer_id.to_string()
-            ))?;
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_cat_range(
+            hash,
+            offset,
+            length,
+            local_maddr.to_string(),
+            config.timeout_for("get"),
+        ),
+        Err(e) => Err(e).into(),
+    }
+}
+
+/// Same as `cat_range`, but saves the slice to the particle vault and returns path to it.
+/// `length` is limited by the maximum download size instead of the inline one.
+#[marine]
+pub fn get_range(hash: String, offset: u64, length: u64) -> IpfsGetResult {
+    log::info!(
+        "get_range called with hash: {}, offset: {}, length: {}",
+        hash,
+        offset,
+        length
+    );
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    let result: eyre::Result<String> = try {
+        validate_range(&hash, length)?;
+        if config.max_download_size != 0 && length > config.max_download_size {
+            Err(ErrorKind::InvalidArgument.error(format!(
+                "length {} exceeds maximum download size of {} bytes",
+                length, config.max_download_size
+            )))?;
         }
 
-        multiaddr.push(peer_id);
-        save_external_api_multiaddr(multiaddr);
-        ()
+        let particle_id = marine_rs_sdk::get_call_parameters().particle_id;
+        let path = format!("/tmp/vault/{}/{}_{}_{}", particle_id, hash, offset, length);
+        let local_maddr = load_local_api_multiaddr()?;
+        let get_result = ipfs_get_range(
+            hash,
+            offset,
+            length,
+            path.clone(),
+            local_maddr.to_string(),
+            config.timeout_for("get"),
+        );
+        if !get_result.success {
+            Err(effector_error(get_result.error, &get_result.error_kind))?;
+        }
+
+        record_vault_write(&path);
+        path
     };
 
     result.into()
 }
 
+fn validate_range(hash: &str, length: u64) -> eyre::Result<()> {
+    validate_cid(hash)?;
+    if length == 0 {
+        Err(ErrorKind::InvalidArgument.error("length must be greater than 0"))?;
+    }
+    Ok(())
+}
+
+/// Same as `cat`, but returns contents as UTF-8 string.
 #[marine]
-pub fn get_local_api_multiaddr() -> IpfsMultiaddrResult {
-    load_local_api_multiaddr().map(|m| m.to_string()).into()
+pub fn get_string(hash: String) -> IpfsGetStringResult {
+    let cat_result = cat(hash);
+    if !cat_result.success {
+        return Err(effector_error(cat_result.error, &cat_result.error_kind)).into();
+    }
+
+    String::from_utf8(cat_result.contents)
+        .map_err(|e| ErrorKind::Utf8.error(format!("contents are not valid UTF-8: {}", e)))
+        .into()
 }
 
+/// DAG get node by hash from local IPFS, encoded as dag-json.
 #[marine]
-pub fn set_local_api_multiaddr(multiaddr: String) -> IpfsResult {
-    if load_local_api_multiaddr().is_ok() {
-        return eyre::Result::<()>::Err(eyre::eyre!("local api multiaddr can only be set once"))
-            .into();
+pub fn dag_get(hash: String) -> IpfsGetResult {
+    dag_get_with_options(hash, "".to_string(), DEFAULT_DAG_OUTPUT_CODEC.to_string())
+}
+
+/// DAG get node by hash and IPLD `path` inside it (e.g. `foo/bar`, empty for the root)
+/// from local IPFS, encoded with `output_codec`.
+#[marine]
+pub fn dag_get_with_options(hash: String, path: String, output_codec: String) -> IpfsGetResult {
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => {
+            dag_get_from_with_options(hash, path, output_codec, local_maddr.to_string())
+        }
+        Err(e) => Err(e).into(),
     }
+}
 
-    let call_parameters = marine_rs_sdk::get_call_parameters();
-    if call_parameters.init_peer_id != call_parameters.service_creator_peer_id {
-        return eyre::Result::<()>::Err(eyre::eyre!(
-            "only service creator can set local api multiaddr"
-        ))
+#[marine]
+pub fn get_from(hash: String, external_multiaddr: String) -> IpfsGetResult {
+    log::info!("get from called with hash: {}", hash);
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    get_from_with_timeout(
+        hash,
+        external_multiaddr,
+        config.timeout_for("get"),
+        &config.retry,
+    )
+}
+
+fn get_from_with_timeout(
+    hash: String,
+    external_multiaddr: String,
+    timeout: u64,
+    retry: &RetryPolicy,
+) -> IpfsGetResult {
+    let particle_id = marine_rs_sdk::get_call_parameters().particle_id;
+    if Multiaddr::from_str(&external_multiaddr).is_err() {
+        return Err(
+            ErrorKind::InvalidMultiaddr.error(format!("invalid multiaddr: {}", external_multiaddr))
+        )
         .into();
     }
 
-    let result: eyre::Result<()> = try {
-        save_local_api_multiaddr(
-            Multiaddr::from_str(&multiaddr)
-                .wrap_err(format!("invalid multiaddr: {}", multiaddr))?,
+    if let Err(e) = validate_cid(&hash) {
+        return Err(e).into();
+    }
+
+    if let Err(e) = check_download_size(&hash, &external_multiaddr, timeout) {
+        return Err(e).into();
+    }
+
+    let particle_vault_path = format!("/tmp/vault/{}", particle_id);
+    let path = format!("{}/{}", particle_vault_path, hash);
+    let get_result = with_retry(retry, |attempt| {
+        if attempt > 1 {
+            remove_vault_path(&path);
+        }
+        ipfs_get(
+            hash.clone(),
+            path.clone(),
+            external_multiaddr.clone(),
+            timeout,
         )
+    });
+
+    let mut result: IpfsGetResult = if get_result.success {
+        check_downloaded_size(&path).map(|_| path).into()
+    } else {
+        Err(effector_error(get_result.error, &get_result.error_kind)).into()
     };
+    result.attempts = get_result.attempts;
+    result
+}
 
-    result.into()
+/// Check cumulative size of the content before downloading it to the vault.
+/// The size is reported by the DAG itself, so the download is checked again afterwards.
+/// Only `files stat` is used: `dag stat` would fetch the whole DAG, so objects that
+/// aren't UnixFS are rejected, `ipfs get` can't save them anyway.
+fn check_download_size(hash: &str, api_multiaddr: &str, timeout: u64) -> eyre::Result<()> {
+    let max_download_size = load_config()?.max_download_size;
+    if max_download_size == 0 {
+        return Ok(());
+    }
+
+    let stat_result = ipfs_stat(hash.to_string(), api_multiaddr.to_string(), timeout);
+    if stat_result.error_kind == ErrorKind::IpfsCliFailed.as_str() {
+        Err(ErrorKind::InvalidArgument.error(format!(
+            "size of {} can't be checked, it isn't a UnixFS object: {}",
+            hash, stat_result.error
+        )))?;
+    }
+    if !stat_result.success {
+        Err(effector_error(stat_result.error, &stat_result.error_kind))?;
+    }
+    if stat_result.cumulative_size > max_download_size {
+        Err(ErrorKind::InvalidArgument.error(format!(
+            "size of {} is {} bytes, exceeds maximum download size of {} bytes",
+            hash, stat_result.cumulative_size, max_download_size
+        )))?;
+    }
+
+    Ok(())
 }
 
-#[marine]
-pub fn get_external_swarm_multiaddr() -> IpfsMultiaddrResult {
-    load_config()
-        .external_swarm_multiaddr
-        .ok_or(eyre::eyre!("multiaddr is not set"))
-        .map(|m| m.to_string())
-        .into()
+/// Measure what was actually written to the vault at `path` and remove it if it's over
+/// the maximum download size, otherwise account it to the particle.
+fn check_downloaded_size(path: &str) -> eyre::Result<()> {
+    let max_download_size = load_config()?.max_download_size;
+    if max_download_size != 0 {
+        let size =
+            usage::disk_usage(Path::new(path)).wrap_err(format!("failed to measure {}", path))?;
+        if size > max_download_size {
+            remove_vault_path(path);
+            Err(ErrorKind::InvalidArgument.error(format!(
+                "downloaded {} bytes to {}, exceeds maximum download size of {} bytes",
+                size, path, max_download_size
+            )))?;
+        }
+    }
+
+    record_vault_write(path);
+    Ok(())
 }
 
+/// Type, size and local availability of the object, so that callers can decide whether
+/// to download it. Objects that aren't UnixFS are measured with `ipfs dag stat`,
+/// which fetches the whole DAG.
 #[marine]
-pub fn set_external_swarm_multiaddr(multiaddr: String) -> IpfsResult {
-    if load_config().external_swarm_multiaddr.is_some() {
-        return eyre::Result::<()>::Err(eyre::eyre!(
-            "external swarm multiaddr can only be set once"
-        ))
-        .into();
+pub fn stat(hash: String) -> IpfsStatResult {
+    log::info!("stat called with hash: {}", hash);
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("get"),
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => stat_with_api(hash, local_maddr.to_string(), timeout),
+        Err(e) => Err(e).into(),
     }
+}
 
-    let call_parameters = marine_rs_sdk::get_call_parameters();
-    if call_parameters.init_peer_id != call_parameters.service_creator_peer_id {
-        return eyre::Result::<()>::Err(eyre::eyre!(
-            "only service creator can set external swarm multiaddr"
-        ))
+/// Same as `stat`, but asks the IPFS node at `external_multiaddr`,
+/// so `local` and `size_local` describe the repo of that node, not of the local one.
+#[marine]
+pub fn stat_from(hash: String, external_multiaddr: String) -> IpfsStatResult {
+    log::info!("stat_from called with hash: {}", hash);
+    if Multiaddr::from_str(&external_multiaddr).is_err() {
+        return Err(
+            ErrorKind::InvalidMultiaddr.error(format!("invalid multiaddr: {}", external_multiaddr))
+        )
         .into();
     }
 
-    let result: eyre::Result<()> = try {
-        let mut config = load_config();
-
-        let mut multiaddr = Multiaddr::from_str(&multiaddr)
-            .wrap_err(format!("invalid multiaddr: {}", multiaddr))?;
-        let local_maddr = load_local_api_multiaddr()?.to_string();
-
-        let mut passed_peer_id = None;
-        match multiaddr.iter().count() {
-            3 => {
-                passed_peer_id = multiaddr.pop();
-            }
-            2 => {}
-            n => Err(eyre::eyre!(
-                "multiaddr should contain 2 or 3 components, {} given",
-                n
-            ))?,
-        }
+    match load_config() {
+        Ok(config) => stat_with_api(hash, external_multiaddr, config.timeout_for("get")),
+        Err(e) => Err(e).into(),
+    }
+}
 
-        let peer_id = get_peer_id(local_maddr.clone(), config.timeout)?;
-        if passed_peer_id.is_some() && passed_peer_id != Some(peer_id.clone()) {
-            Err(eyre::eyre!(
-                "given peer id is different from node peer_id: given {}, actual {}",
-                passed_peer_id.unwrap().to_string(),
-                peer_id.to_string()
-            ))?;
-        }
+fn stat_with_api(hash: String, api_multiaddr: String, timeout: u64) -> IpfsStatResult {
+    if let Err(e) = validate_cid(&hash) {
+        return Err(e).into();
+    }
 
-        multiaddr.push(peer_id);
-        config.external_swarm_multiaddr = Some(multiaddr);
-        write_config(config)
-    };
+    let result = ipfs_stat(hash.clone(), api_multiaddr.clone(), timeout);
+    // other kinds, e.g. timeouts or missing objects, won't be fixed by dag stat
+    if result.success || result.error_kind != ErrorKind::IpfsCliFailed.as_str() {
+        return result;
+    }
 
-    result.into()
+    log::info!(
+        "files stat of {} failed, trying dag stat: {}",
+        hash,
+        result.error
+    );
+    ipfs_dag_stat(hash, api_multiaddr, timeout)
 }
 
+/// Bytes written to particle vaults by this service: in the current particle,
+/// on behalf of its init peer and in total.
 #[marine]
-pub fn set_timeout(timeout_sec: u64) {
-    let mut config = load_config();
-    config.timeout = timeout_sec;
-    write_config(config);
+pub fn vault_usage() -> IpfsVaultUsageResult {
+    usage::vault_usage().into()
 }
 
+/// Get file by hash from the first of `external_multiaddrs` that serves it.
+///
+/// Sources are tried one by one with the configured timeout each. "failover" is the only
+/// `strategy` for now, empty string selects it too: calls to IPFS can't run concurrently
+/// inside the service, so strategies like racing the sources aren't possible.
+/// The retry policy isn't applied to individual sources.
+/// Returns the source that served the content and errors of the sources tried before it.
 #[marine]
-#[link(wasm_import_module = "ipfs_effector")]
-extern "C" {
-    #[link_name = "connect"]
-    pub fn ipfs_connect(
+pub fn get_from_any(
+    hash: String,
+    external_multiaddrs: Vec<String>,
+    strategy: String,
+) -> IpfsGetFromAnyResult {
+    log::info!(
+        "get_from_any called with hash: {}, sources: {:?}, strategy: {:?}",
+        hash,
+        external_multiaddrs,
+        strategy
+    );
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("get"),
+        Err(e) => return Err(e).into(),
+    };
+
+    let validated: eyre::Result<()> = try {
+        validate_cid(&hash)?;
+        if external_multiaddrs.is_empty() {
+            Err(ErrorKind::InvalidArgument.error("no sources to get from"))?;
+        }
+        if !matches!(strategy.as_str(), "" | "failover") {
+            Err(ErrorKind::InvalidArgument
+                .error(format!("unknown strategy {}, expected failover", strategy)))?;
+        }
+    };
+    if let Err(e) = validated {
+        return Err(e).into();
+    }
+
+    let mut source_errors = vec![];
+    for source in &external_multiaddrs {
+        // trying the next source is the retry here, retrying every source would multiply attempts
+        let result = get_from_with_timeout(
+            hash.clone(),
+            source.clone(),
+            timeout,
+            &RetryPolicy::default(),
+        );
+        if result.success {
+            let mut any_result: IpfsGetFromAnyResult = Ok((result.path, source.clone())).into();
+            any_result.source_errors = source_errors;
+            return any_result;
+        }
+
+        log::warn!("failed to get {} from {}: {}", hash, source, result.error);
+        // a partial download must not be mistaken for the content served by the next source
+        remove_vault_path(&format!("{}/{}", get_particle_vault_path(), hash));
+        source_errors.push(IpfsSourceError {
+            multiaddr: source.clone(),
+            error: result.error,
+            error_kind: result.error_kind,
+        });
+    }
+
+    // the kind of the last error tells whether content is missing or sources are unreachable
+    let last_kind = source_errors
+        .last()
+        .map(|e| ErrorKind::parse(&e.error_kind))
+        .unwrap_or(ErrorKind::NotFound);
+    let mut any_result: IpfsGetFromAnyResult = Err(last_kind.error(format!(
+        "failed to get {} from any of {} sources",
+        hash,
+        external_multiaddrs.len()
+    )))
+    .into();
+    any_result.source_errors = source_errors;
+    any_result
+}
+
+fn remove_vault_path(path: &str) {
+    let removed = match fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(_) => return,
+    };
+    if let Err(e) = removed {
+        log::warn!("failed to remove {}: {}", path, e);
+    }
+}
+
+/// List entries of UnixFS directory without downloading it: names, CIDs, sizes and types.
+/// At most `DEFAULT_LS_MAX_ENTRIES` entries are returned, `truncated` is set if there are more.
+#[marine]
+pub fn ls(hash: String) -> IpfsLsResult {
+    ls_with_options(hash, true, DEFAULT_LS_MAX_ENTRIES)
+}
+
+/// Same as `ls`, but sizes and types of entries are resolved only if `resolve_type` is set,
+/// which fetches a block of every entry. `max_entries` 0 means no limit. The limit only bounds
+/// the size of the result: IPFS lists, and resolves if asked to, the whole directory anyway,
+/// so for large directories consider turning `resolve_type` off.
+#[marine]
+pub fn ls_with_options(hash: String, resolve_type: bool, max_entries: u64) -> IpfsLsResult {
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => {
+            ls_from_with_options(hash, resolve_type, max_entries, local_maddr.to_string())
+        }
+        Err(e) => Err(e).into(),
+    }
+}
+
+#[marine]
+pub fn ls_from(hash: String, external_multiaddr: String) -> IpfsLsResult {
+    ls_from_with_options(hash, true, DEFAULT_LS_MAX_ENTRIES, external_multiaddr)
+}
+
+/// Same as `ls_with_options`, but from the IPFS node at `external_multiaddr`.
+#[marine]
+pub fn ls_from_with_options(
+    hash: String,
+    resolve_type: bool,
+    max_entries: u64,
+    external_multiaddr: String,
+) -> IpfsLsResult {
+    log::info!(
+        "ls_from_with_options called with hash: {}, resolve_type: {}, max_entries: {}",
+        hash,
+        resolve_type,
+        max_entries
+    );
+    if Multiaddr::from_str(&external_multiaddr).is_err() {
+        return Err(
+            ErrorKind::InvalidMultiaddr.error(format!("invalid multiaddr: {}", external_multiaddr))
+        )
+        .into();
+    }
+
+    if let Err(e) = validate_cid(&hash) {
+        return Err(e).into();
+    }
+
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("get"),
+        Err(e) => return Err(e).into(),
+    };
+    ipfs_ls(hash, resolve_type, max_entries, external_multiaddr, timeout)
+}
+
+/// Create MFS directory at `path` along with missing parents, return CID of the service MFS root.
+/// MFS paths of all `files_*` functions are relative to the root of this service.
+/// Functions changing MFS are restricted to admins.
+#[marine]
+pub fn files_mkdir(path: String) -> IpfsCidResult {
+    log::info!("files_mkdir called with path: {}", path);
+    let path = match mfs_path(&path) {
+        Ok(path) => path,
+        Err(e) => return Err(e).into(),
+    };
+    mfs_mutation(|api_multiaddr, timeout| ipfs_files_mkdir(path, true, api_multiaddr, timeout))
+}
+
+/// Copy content by `hash` to MFS `path` without downloading it, return CID of the MFS root.
+/// Parent directory of `path` must exist.
+#[marine]
+pub fn files_cp(hash: String, path: String) -> IpfsCidResult {
+    log::info!("files_cp called with hash: {}, path: {}", hash, path);
+    let path = match validate_cid(&hash).and_then(|_| mfs_entry_path(&path)) {
+        Ok(path) => path,
+        Err(e) => return Err(e).into(),
+    };
+    mfs_mutation(|api_multiaddr, timeout| {
+        ipfs_files_cp(format!("/ipfs/{}", hash), path, api_multiaddr, timeout)
+    })
+}
+
+/// Write vault file at `file_path` to MFS `path`, replacing its contents,
+/// return CID of the MFS root.
+#[marine]
+pub fn files_write(path: String, file_path: String) -> IpfsCidResult {
+    log::info!(
+        "files_write called with path: {}, file: {}",
+        path,
+        file_path
+    );
+    let path = match mfs_entry_path(&path) {
+        Ok(path) => path,
+        Err(e) => return Err(e).into(),
+    };
+    mfs_mutation(|api_multiaddr, timeout| ipfs_files_write(path, file_path, api_multiaddr, timeout))
+}
+
+/// Read MFS file at `path`. Fails if it's larger than the configured maximum inline size.
+#[marine]
+pub fn files_read(path: String) -> IpfsCatResult {
+    log::info!("files_read called with path: {}", path);
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    let result: eyre::Result<Vec<u8>> = try {
+        let path = mfs_entry_path(&path)?;
+        let local_maddr = load_local_api_multiaddr()?;
+        // request one extra byte to detect contents exceeding max_inline_size
+        let read_result = ipfs_files_read(
+            path,
+            0,
+            config.max_inline_size.saturating_add(1),
+            local_maddr.to_string(),
+            config.timeout_for("files"),
+        );
+        if !read_result.success {
+            Err(effector_error(read_result.error, &read_result.error_kind))?;
+        }
+        if read_result.contents.len() as u64 > config.max_inline_size {
+            Err(ErrorKind::InvalidArgument.error(format!(
+                "contents size exceeds maximum of {} bytes",
+                config.max_inline_size
+            )))?;
+        }
+        read_result.contents
+    };
+
+    result.into()
+}
+
+/// List MFS directory at `path`, empty `path` lists the service MFS root.
+#[marine]
+pub fn files_ls(path: String) -> IpfsLsResult {
+    log::info!("files_ls called with path: {}", path);
+    let result: eyre::Result<(String, String, u64)> = try {
+        let path = mfs_path(&path)?;
+        let (api_multiaddr, timeout) = ensure_mfs_root()?;
+        (path, api_multiaddr, timeout)
+    };
+
+    match result {
+        Ok((path, api_multiaddr, timeout)) => ipfs_files_ls(path, api_multiaddr, timeout),
+        Err(e) => Err(e).into(),
+    }
+}
+
+/// Remove MFS file or directory at `path`, directories only if `recursive` is set.
+/// Return CID of the MFS root.
+#[marine]
+pub fn files_rm(path: String, recursive: bool) -> IpfsCidResult {
+    log::info!("files_rm called with path: {}", path);
+    let path = match mfs_entry_path(&path) {
+        Ok(path) => path,
+        Err(e) => return Err(e).into(),
+    };
+    mfs_mutation(|api_multiaddr, timeout| ipfs_files_rm(path, recursive, api_multiaddr, timeout))
+}
+
+/// Move MFS file or directory from `source` to `dest`, return CID of the MFS root.
+#[marine]
+pub fn files_mv(source: String, dest: String) -> IpfsCidResult {
+    log::info!("files_mv called with {} -> {}", source, dest);
+    let paths = mfs_entry_path(&source).and_then(|source| Ok((source, mfs_entry_path(&dest)?)));
+    let (source, dest) = match paths {
+        Ok(paths) => paths,
+        Err(e) => return Err(e).into(),
+    };
+    mfs_mutation(|api_multiaddr, timeout| ipfs_files_mv(source, dest, api_multiaddr, timeout))
+}
+
+/// CID, size and type of MFS file or directory at `path`, empty `path` for the MFS root.
+#[marine]
+pub fn files_stat(path: String) -> IpfsStatResult {
+    log::info!("files_stat called with path: {}", path);
+    let result: eyre::Result<(String, String, u64)> = try {
+        let path = mfs_path(&path)?;
+        let (api_multiaddr, timeout) = ensure_mfs_root()?;
+        (path, api_multiaddr, timeout)
+    };
+
+    match result {
+        Ok((path, api_multiaddr, timeout)) => ipfs_files_stat(path, api_multiaddr, timeout),
+        Err(e) => Err(e).into(),
+    }
+}
+
+/// Flush the service MFS root to the blockstore and return its CID.
+#[marine]
+pub fn files_flush() -> IpfsCidResult {
+    let result: eyre::Result<String> = try {
+        let (api_multiaddr, timeout) = ensure_mfs_root()?;
+        let flush_result = ipfs_files_flush(mfs_root(), api_multiaddr, timeout);
+        if !flush_result.success {
+            Err(effector_error(flush_result.error, &flush_result.error_kind))?;
+        }
+        flush_result.cid
+    };
+
+    result.into()
+}
+
+fn mfs_root() -> String {
+    let service_id = marine_rs_sdk::get_call_parameters().service_id;
+    format!("{}/{}", MFS_ROOT_PREFIX, service_id)
+}
+
+/// Resolve `path` relative to the service MFS root. `..` is rejected,
+/// so that services can't reach each other's files.
+fn mfs_path(path: &str) -> eyre::Result<String> {
+    let mut resolved = mfs_root();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(name) => {
+                resolved.push('/');
+                resolved.push_str(&name.to_string_lossy());
+            }
+            Component::RootDir | Component::CurDir => {}
+            Component::ParentDir | Component::Prefix(_) => {
+                Err(ErrorKind::InvalidArgument
+                    .error(format!("MFS path {} must not contain ..", path)))?
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+/// Same as `mfs_path`, but `path` must point inside the root rather than at the root itself.
+fn mfs_entry_path(path: &str) -> eyre::Result<String> {
+    let resolved = mfs_path(path)?;
+    if resolved == mfs_root() {
+        Err(ErrorKind::InvalidArgument.error("MFS path must not be empty"))?;
+    }
+    Ok(resolved)
+}
+
+/// Create the service MFS root if it's missing, return API multiaddr and timeout for MFS calls.
+fn ensure_mfs_root() -> eyre::Result<(String, u64)> {
+    let api_multiaddr = load_local_api_multiaddr()?.to_string();
+    let timeout = load_config()?.timeout_for("files");
+    let mkdir_result = ipfs_files_mkdir(mfs_root(), true, api_multiaddr.clone(), timeout);
+    if !mkdir_result.success {
+        Err(effector_error(mkdir_result.error, &mkdir_result.error_kind))?;
+    }
+    Ok((api_multiaddr, timeout))
+}
+
+/// Run MFS mutation and return CID of the service MFS root after it.
+/// The root is shared by all callers of the service, so only admins can change it.
+fn mfs_mutation(mutate: impl FnOnce(String, u64) -> IpfsResult) -> IpfsCidResult {
+    let result: eyre::Result<String> = try {
+        check_admin("change MFS")?;
+        let (api_multiaddr, timeout) = ensure_mfs_root()?;
+        let mutation_result = mutate(api_multiaddr.clone(), timeout);
+        if !mutation_result.success {
+            Err(effector_error(
+                mutation_result.error,
+                &mutation_result.error_kind,
+            ))?;
+        }
+
+        let stat_result = ipfs_files_stat(mfs_root(), api_multiaddr, timeout);
+        if !stat_result.success {
+            Err(effector_error(stat_result.error, &stat_result.error_kind))?;
+        }
+        stat_result.hash
+    };
+
+    result.into()
+}
+
+/// Same as `get`, but also returns manifest of the downloaded tree.
+#[marine]
+pub fn get_with_manifest(hash: String) -> IpfsGetManifestResult {
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => get_from_with_manifest(hash, local_maddr.to_string()),
+        Err(e) => Err(e).into(),
+    }
+}
+
+/// Same as `get_from`, but also returns manifest: paths relative to the downloaded root,
+/// sizes and CIDs of the root and all files and directories under it.
+#[marine]
+pub fn get_from_with_manifest(hash: String, external_multiaddr: String) -> IpfsGetManifestResult {
+    log::info!("get_from_with_manifest called with hash: {}", hash);
+    let get_result = get_from(hash.clone(), external_multiaddr.clone());
+    if !get_result.success {
+        return Err(effector_error(get_result.error, &get_result.error_kind)).into();
+    }
+
+    let result: eyre::Result<(String, Vec<IpfsManifestEntry>)> = try {
+        let metadata = fs::metadata(&get_result.path)
+            .wrap_err(format!("failed to read metadata of {}", get_result.path))?;
+        let mut entries = vec![IpfsManifestEntry {
+            path: "".to_string(),
+            hash: hash.clone(),
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+            is_dir: metadata.is_dir(),
+        }];
+        if metadata.is_dir() {
+            let timeout = load_config()?.timeout_for("get");
+            collect_manifest(&hash, "", &external_multiaddr, timeout, &mut entries)?;
+        }
+        (get_result.path, entries)
+    };
+
+    result.into()
+}
+
+fn collect_manifest(
+    hash: &str,
+    prefix: &str,
+    api_multiaddr: &str,
+    timeout: u64,
+    entries: &mut Vec<IpfsManifestEntry>,
+) -> eyre::Result<()> {
+    let ls_result = ipfs_ls(
+        hash.to_string(),
+        true,
+        0,
+        api_multiaddr.to_string(),
+        timeout,
+    );
+    if !ls_result.success {
+        Err(effector_error(ls_result.error, &ls_result.error_kind))?;
+    }
+
+    for entry in ls_result.entries {
+        let path = if prefix.is_empty() {
+            entry.name
+        } else {
+            format!("{}/{}", prefix, entry.name)
+        };
+        let is_dir = entry.entry_type == "directory";
+        entries.push(IpfsManifestEntry {
+            path: path.clone(),
+            hash: entry.hash.clone(),
+            size: entry.size,
+            is_dir,
+        });
+        if is_dir {
+            collect_manifest(&entry.hash, &path, api_multiaddr, timeout, entries)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Same as `get_from`, but re-chunks and re-hashes downloaded file locally and fails if
+/// computed root CID differs from the requested one, removing the downloaded file.
+/// `chunker` (e.g. "size-262144", empty for default) and `raw_leaves` must match
+/// the parameters the file was added with. CID version and hash function are taken from `hash`.
+/// Same limits as in `verify_file` apply.
+#[marine]
+pub fn get_from_verified(
+    hash: String,
+    external_multiaddr: String,
+    chunker: String,
+    raw_leaves: bool,
+) -> IpfsGetResult {
+    log::info!("get_from_verified called with hash: {}", hash);
+    let get_result = get_from(hash.clone(), external_multiaddr);
+    if !get_result.success {
+        return get_result;
+    }
+
+    let verified = verify_file_cid(&get_result.path, &hash, &chunker, raw_leaves);
+    if verified.is_err() {
+        remove_vault_path(&get_result.path);
+    }
+
+    verified.map(|_| get_result.path).into()
+}
+
+/// Re-chunk and re-hash local file at `file_path` and check that its root CID is `hash`,
+/// e.g. for a file downloaded earlier. `chunker` and `raw_leaves` are the same as in
+/// `get_from_verified`. Only files added the way `ipfs add` does by default can be verified:
+/// - fixed-size chunker, `rabin` and `buzhash` are rejected with `InvalidArgument`;
+/// - balanced layout, files added with `--trickle` fail with `IntegrityCheckFailed`;
+/// - sha2-256 hash function, other ones are rejected with `InvalidArgument`;
+/// - regular files, directories are rejected with `InvalidArgument`.
+#[marine]
+pub fn verify_file(
+    file_path: String,
+    hash: String,
+    chunker: String,
+    raw_leaves: bool,
+) -> IpfsResult {
+    log::info!("verify_file called with {:?} and hash: {}", file_path, hash);
+    verify_file_cid(&file_path, &hash, &chunker, raw_leaves).into()
+}
+
+fn verify_file_cid(path: &str, hash: &str, chunker: &str, raw_leaves: bool) -> eyre::Result<()> {
+    let (expected, _) = Cid::parse(hash)
+        .map_err(|e| ErrorKind::InvalidCid.error(format!("invalid cid {}: {}", hash, e)))?;
+    if expected.hash.code() != MULTIHASH_SHA2_256 {
+        Err(ErrorKind::InvalidArgument.error(format!(
+            "only sha2-256 files can be verified, {} uses {}",
+            hash,
+            multihash_name(expected.hash.code())
+        )))?;
+    }
+    let metadata = fs::metadata(path)
+        .map_err(|e| ErrorKind::NotFound.error(format!("failed to read {}: {}", path, e)))?;
+    if metadata.is_dir() {
+        Err(ErrorKind::InvalidArgument.error(format!(
+            "only files can be verified, {} is a directory",
+            hash
+        )))?;
+    }
+
+    let params = ImportParams {
+        cid_version: expected.version,
+        hash: expected.hash.code(),
+        chunk_size: parse_chunker(chunker)
+            .map_err(|e| ErrorKind::InvalidArgument.error(format!("{:?}", e)))?,
+        raw_leaves,
+    };
+    let file = fs::File::open(path).wrap_err(format!("failed to open {}", path))?;
+    let computed = file_cid(BufReader::new(file), &params)?;
+    if computed != expected {
+        Err(ErrorKind::IntegrityCheckFailed.error(format!(
+            "integrity check failed: requested {}, computed {}",
+            hash, computed
+        )))?;
+    }
+
+    Ok(())
+}
+
+/// DAG get node by hash from the IPFS node at `external_multiaddr`, encoded as dag-json.
+#[marine]
+pub fn dag_get_from(hash: String, external_multiaddr: String) -> IpfsGetResult {
+    dag_get_from_with_options(
+        hash,
+        "".to_string(),
+        DEFAULT_DAG_OUTPUT_CODEC.to_string(),
+        external_multiaddr,
+    )
+}
+
+/// Same as `dag_get_with_options`, but from the IPFS node at `external_multiaddr`.
+/// Returns path to the file in the particle vault.
+#[marine]
+pub fn dag_get_from_with_options(
+    hash: String,
+    path: String,
+    output_codec: String,
+    external_multiaddr: String,
+) -> IpfsGetResult {
+    log::info!(
+        "dag_get_from_with_options called with hash: {}, path: {:?}",
+        hash,
+        path
+    );
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    let timeout = config.timeout_for("dag");
+
+    let particle_id = marine_rs_sdk::get_call_parameters().particle_id;
+    if Multiaddr::from_str(&external_multiaddr).is_err() {
+        return Err(
+            ErrorKind::InvalidMultiaddr.error(format!("invalid multiaddr: {}", external_multiaddr))
+        )
+        .into();
+    }
+
+    let result: eyre::Result<()> = try {
+        validate_cid(&hash)?;
+        validate_dag_codec(&output_codec)?;
+    };
+    if let Err(e) = result {
+        return Err(e).into();
+    }
+
+    let particle_vault_path = format!("/tmp/vault/{}", particle_id);
+    // IPLD path segments are flattened, so that the file stays directly in the vault
+    let file_name = if path.trim_matches('/').is_empty() {
+        hash.clone()
+    } else {
+        format!("{}_{}", hash, path.trim_matches('/').replace('/', "_"))
+    };
+    let file_path = format!("{}/{}", particle_vault_path, file_name);
+    let get_result = with_retry(&config.retry, |_| {
+        ipfs_dag_get(
+            hash.clone(),
+            path.clone(),
+            output_codec.clone(),
+            file_path.clone(),
+            external_multiaddr.clone(),
+            timeout,
+        )
+    });
+
+    let mut result: IpfsGetResult = if get_result.success {
+        check_downloaded_size(&file_path).map(|_| file_path).into()
+    } else {
+        Err(effector_error(get_result.error, &get_result.error_kind)).into()
+    };
+    result.attempts = get_result.attempts;
+    result
+}
+
+/// Export DAG rooted at `hash` from local IPFS as CAR file in the particle vault.
+/// Returns path to the file.
+#[marine]
+pub fn dag_export(hash: String) -> IpfsGetResult {
+    log::info!("dag_export called with hash: {}", hash);
+    if let Err(e) = validate_cid(&hash) {
+        return Err(e).into();
+    }
+
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    let path = format!("{}/{}.car", get_particle_vault_path(), hash);
+    let export_result = match load_local_api_multiaddr() {
+        Ok(local_maddr) => with_retry(&config.retry, |_| {
+            ipfs_dag_export(
+                hash.clone(),
+                path.clone(),
+                local_maddr.to_string(),
+                config.timeout_for("dag"),
+            )
+        }),
+        Err(e) => return Err(e).into(),
+    };
+
+    let mut result: IpfsGetResult = if export_result.success {
+        check_downloaded_size(&path).map(|_| path).into()
+    } else {
+        Err(effector_error(
+            export_result.error,
+            &export_result.error_kind,
+        ))
+        .into()
+    };
+    result.attempts = export_result.attempts;
+    result
+}
+
+/// Import all blocks of CAR file to local IPFS, pin its roots and return them.
+#[marine]
+pub fn dag_import(file_path: String) -> IpfsDagImportResult {
+    log::info!("dag_import called with {:?}", file_path);
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => with_retry(&config.retry, |_| {
+            ipfs_dag_import(
+                file_path.clone(),
+                local_maddr.to_string(),
+                config.timeout_for("dag"),
+            )
+        }),
+        Err(e) => Err(e).into(),
+    }
+}
+
+/// Read CAR file locally and list its roots and CIDs of all blocks, without calling IPFS.
+#[marine]
+pub fn car_info(file_path: String) -> IpfsCarInfoResult {
+    let result: eyre::Result<IpfsCarInfo> = try {
+        let file = fs::File::open(&file_path).map_err(|e| {
+            ErrorKind::NotFound.error(format!("failed to open {}: {}", file_path, e))
+        })?;
+        let invalid_car = |e: eyre::Report| {
+            ErrorKind::InvalidArgument.error(format!("invalid CAR file {}: {}", file_path, e))
+        };
+
+        let mut reader = CarReader::new(BufReader::new(file)).map_err(invalid_car)?;
+        let mut blocks = vec![];
+        while let Some((cid, _)) = reader.next_block().map_err(invalid_car)? {
+            blocks.push(cid.to_string());
+        }
+
+        IpfsCarInfo {
+            version: reader.version,
+            roots: reader.roots.iter().map(|cid| cid.to_string()).collect(),
+            blocks,
+        }
+    };
+
+    result.into()
+}
+
+/// Verify CAR file locally before importing it: recompute multihash of every block,
+/// check that all roots are present and report the number of blocks and their total size.
+#[marine]
+pub fn car_verify(file_path: String) -> IpfsCarVerifyResult {
+    log::info!("car_verify called with {:?}", file_path);
+    let result: eyre::Result<(Vec<String>, u64, u64)> = try {
+        let file = fs::File::open(&file_path).map_err(|e| {
+            ErrorKind::NotFound.error(format!("failed to open {}: {}", file_path, e))
+        })?;
+        let invalid_car = |e: eyre::Report| {
+            ErrorKind::InvalidArgument.error(format!("invalid CAR file {}: {}", file_path, e))
+        };
+
+        let mut reader = CarReader::new(BufReader::new(file)).map_err(invalid_car)?;
+        let mut present = HashSet::new();
+        let mut block_count = 0u64;
+        let mut total_size = 0u64;
+        while let Some((cid, data)) = reader.next_block().map_err(invalid_car)? {
+            if !verify_block(&cid, &data).map_err(invalid_car)? {
+                Err(ErrorKind::IntegrityCheckFailed.error(format!(
+                    "integrity check failed: block {} doesn't match its hash",
+                    cid
+                )))?;
+            }
+            block_count += 1;
+            total_size += data.len() as u64;
+            present.insert(cid.into_v1());
+        }
+
+        for root in &reader.roots {
+            // identity CIDs carry data inline and don't need a block
+            let inline = root.hash.code() == MULTIHASH_IDENTITY;
+            if !inline && !present.contains(&root.clone().into_v1()) {
+                Err(ErrorKind::IntegrityCheckFailed
+                    .error(format!("integrity check failed: root {} is missing", root)))?;
+            }
+        }
+
+        let roots = reader.roots.iter().map(|cid| cid.to_string()).collect();
+        (roots, block_count, total_size)
+    };
+
+    result.into()
+}
+
+#[marine]
+pub fn pin_add(hash: String, recursive: bool) -> IpfsResult {
+    log::info!("pin_add called with hash: {}", hash);
+    if let Err(e) = validate_cid(&hash) {
+        return Err(e).into();
+    }
+
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("pin"),
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_pin_add(hash, recursive, local_maddr.to_string(), timeout),
+        Err(e) => Err(e).into(),
+    }
+}
+
+/// Unpin object. Pins are shared by all callers of the node, so only admins can remove them.
+#[marine]
+pub fn pin_rm(hash: String, recursive: bool) -> IpfsResult {
+    log::info!("pin_rm called with hash: {}", hash);
+    if let Err(e) = check_admin("remove pin") {
+        return Err(e).into();
+    }
+    if let Err(e) = validate_cid(&hash) {
+        return Err(e).into();
+    }
+
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("pin"),
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_pin_rm(hash, recursive, local_maddr.to_string(), timeout),
+        Err(e) => Err(e).into(),
+    }
+}
+
+/// List local pins. `hash` may be empty to list every pin,
+/// `pin_type` is one of "all", "recursive", "direct" or "indirect".
+#[marine]
+pub fn pin_ls(hash: String, pin_type: String) -> IpfsPinLsResult {
+    log::info!("pin_ls called with hash: {:?}, type: {}", hash, pin_type);
+    if !PIN_TYPES.contains(&pin_type.as_str()) {
+        return Err(ErrorKind::InvalidArgument.error(format!(
+            "invalid pin type: {}, expected one of {:?}",
+            pin_type, PIN_TYPES
+        )))
+        .into();
+    }
+
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("pin"),
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_pin_ls(hash, pin_type, local_maddr.to_string(), timeout),
+        Err(e) => Err(e).into(),
+    }
+}
+
+#[marine]
+pub fn pin_verify() -> IpfsPinVerifyResult {
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("pin"),
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_pin_verify(local_maddr.to_string(), timeout),
+        Err(e) => Err(e).into(),
+    }
+}
+
+#[marine]
+pub fn repo_stat() -> IpfsRepoStatResult {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_repo_stat(local_maddr.to_string(), config.timeout_for("repo")),
+        Err(e) => Err(e).into(),
+    }
+}
+
+/// Remove unpinned blocks from the local repo, return the number of removed blocks.
+#[marine]
+pub fn repo_gc() -> IpfsRepoGcResult {
+    if let Err(e) = check_admin("run repo gc") {
+        return Err(e).into();
+    }
+
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_repo_gc(local_maddr.to_string(), config.timeout_for("repo")),
+        Err(e) => Err(e).into(),
+    }
+}
+
+#[marine]
+pub fn repo_verify() -> IpfsRepoVerifyResult {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_repo_verify(local_maddr.to_string(), config.timeout_for("repo")),
+        Err(e) => Err(e).into(),
+    }
+}
+
+/// Publish IPNS record for `key` pointing to `hash`.
+/// Empty `lifetime` and `ttl` (e.g. "24h", "1m") fall back to IPFS defaults.
+#[marine]
+pub fn name_publish(
+    hash: String,
+    key: String,
+    lifetime: String,
+    ttl: String,
+) -> IpfsNamePublishResult {
+    log::info!("name_publish called with hash: {}, key: {}", hash, key);
+    if let Err(e) = check_admin("publish ipns records") {
+        return Err(e).into();
+    }
+
+    let key = if key.is_empty() {
+        DEFAULT_IPNS_KEY.to_string()
+    } else {
+        key
+    };
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("name"),
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => {
+            ipfs_name_publish(hash, key, lifetime, ttl, local_maddr.to_string(), timeout)
+        }
+        Err(e) => Err(e).into(),
+    }
+}
+
+#[marine]
+pub fn name_resolve(name: String, recursive: bool, nocache: bool) -> IpfsNameResolveResult {
+    log::info!("name_resolve called with name: {}", name);
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("name"),
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => {
+            ipfs_name_resolve(name, recursive, nocache, local_maddr.to_string(), timeout)
+        }
+        Err(e) => Err(e).into(),
+    }
+}
+
+/// Generate a new IPNS key. `key_type` is "ed25519", "rsa" or empty for IPFS default.
+#[marine]
+pub fn key_gen(name: String, key_type: String) -> IpfsKeyResult {
+    log::info!("key_gen called with name: {}", name);
+    if let Err(e) = check_admin("generate keys") {
+        return Err(e).into();
+    }
+
+    if !key_type.is_empty() && !KEY_TYPES.contains(&key_type.as_str()) {
+        return Err(ErrorKind::InvalidArgument.error(format!(
+            "invalid key type: {}, expected one of {:?}",
+            key_type, KEY_TYPES
+        )))
+        .into();
+    }
+
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("key"),
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_key_gen(name, key_type, local_maddr.to_string(), timeout),
+        Err(e) => Err(e).into(),
+    }
+}
+
+#[marine]
+pub fn key_list() -> IpfsKeyListResult {
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("key"),
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_key_list(local_maddr.to_string(), timeout),
+        Err(e) => Err(e).into(),
+    }
+}
+
+#[marine]
+pub fn key_rm(name: String) -> IpfsKeyListResult {
+    log::info!("key_rm called with name: {}", name);
+    if let Err(e) = check_admin("remove keys") {
+        return Err(e).into();
+    }
+
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("key"),
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => ipfs_key_rm(name, local_maddr.to_string(), timeout),
+        Err(e) => Err(e).into(),
+    }
+}
+
+#[marine]
+pub fn key_rename(old_name: String, new_name: String, force: bool) -> IpfsKeyResult {
+    log::info!("key_rename called with {} -> {}", old_name, new_name);
+    if let Err(e) = check_admin("rename keys") {
+        return Err(e).into();
+    }
+
+    let timeout = match load_config() {
+        Ok(config) => config.timeout_for("key"),
+        Err(e) => return Err(e).into(),
+    };
+    match load_local_api_multiaddr() {
+        Ok(local_maddr) => {
+            ipfs_key_rename(old_name, new_name, force, local_maddr.to_string(), timeout)
+        }
+        Err(e) => Err(e).into(),
+    }
+}
+
+/// Parse CID locally and report its version, codec, multihash function and multibase.
+#[marine]
+pub fn cid_info(cid: String) -> IpfsCidInfoResult {
+    Cid::parse(&cid)
+        .map(|(parsed, base)| IpfsCidInfo {
+            version: parsed.version,
+            codec: codec_name(parsed.codec),
+            multihash: multihash_name(parsed.hash.code()),
+            digest_size: parsed.hash.size() as u32,
+            multibase: base.name().to_string(),
+        })
+        .map_err(|e| ErrorKind::InvalidCid.error(format!("invalid cid {}: {}", cid, e)))
+        .into()
+}
+
+/// Convert dag-pb sha2-256 CID to CIDv0.
+#[marine]
+pub fn cid_to_v0(cid: String) -> IpfsCidResult {
+    let result: eyre::Result<String> = try {
+        let (parsed, _) = Cid::parse(&cid)
+            .map_err(|e| ErrorKind::InvalidCid.error(format!("invalid cid {}: {}", cid, e)))?;
+        parsed.into_v0()?.to_string()
+    };
+
+    result.into()
+}
+
+/// Convert CID to CIDv1 encoded with provided multibase, e.g. "base32" or "base36".
+/// Empty multibase defaults to base32.
+#[marine]
+pub fn cid_to_v1(cid: String, multibase: String) -> IpfsCidResult {
+    let result: eyre::Result<String> = try {
+        let (parsed, _) = Cid::parse(&cid)
+            .map_err(|e| ErrorKind::InvalidCid.error(format!("invalid cid {}: {}", cid, e)))?;
+        let base = if multibase.is_empty() {
+            Multibase::Base32Lower
+        } else {
+            Multibase::from_name(&multibase)?
+        };
+        parsed.into_v1().to_string_of_base(base)?
+    };
+
+    result.into()
+}
+
+fn validate_cid(cid: &str) -> eyre::Result<()> {
+    Cid::parse(cid)
+        .map_err(|e| ErrorKind::InvalidCid.error(format!("invalid cid {}: {}", cid, e)))?;
+    Ok(())
+}
+
+#[marine]
+pub fn get_external_api_multiaddr() -> IpfsMultiaddrResult {
+    load_external_api_multiaddr().map(|m| m.to_string()).into()
+}
+
+#[marine]
+pub fn set_external_api_multiaddr(multiaddr: String) -> IpfsResult {
+    if let Ok(current) = load_external_api_multiaddr() {
+        if is_same_multiaddr(&current, &multiaddr) {
+            return eyre::Result::<()>::Ok(()).into();
+        }
+        return eyre::Result::<()>::Err(
+            ErrorKind::InvalidArgument.error("external api multiaddr can only be set once"),
+        )
+        .into();
+    }
+
+    if let Err(e) = check_admin("set external api multiaddr") {
+        return Err(e).into();
+    }
+
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => return Err(e).into(),
+    };
+    let timeout = config.timeout_for("id");
+
+    let result: eyre::Result<()> = try {
+        let mut multiaddr = Multiaddr::from_str(&multiaddr).map_err(|_| {
+            ErrorKind::InvalidMultiaddr.error(format!("invalid multiaddr: {}", multiaddr))
+        })?;
+        let local_maddr = load_local_api_multiaddr()?.to_string();
+        let mut passed_peer_id = None;
+        match multiaddr.iter().count() {
+            3 => {
+                passed_peer_id = multiaddr.pop();
+            }
+            2 => {}
+            n => Err(ErrorKind::InvalidMultiaddr.error(format!(
+                "multiaddr should contain 2 or 3 components, {} given",
+                n
+            )))?,
+        }
+
+        let peer_id = get_peer_id(local_maddr, timeout)?;
+        if passed_peer_id.is_some() && passed_peer_id != Some(peer_id.clone()) {
+            Err(ErrorKind::InvalidMultiaddr.error(format!(
+                "given peer id is different from node peer_id: given {}, actual {}",
+                passed_peer_id.unwrap(),
+                peer_id
+            )))?;
+        }
+
+        multiaddr.push(peer_id);
+        save_external_api_multiaddr(multiaddr)?
+    };
+
+    result.into()
+}
+
+#[marine]
+pub fn get_local_api_multiaddr() -> IpfsMultiaddrResult {
+    load_local_api_multiaddr().map(|m| m.to_string()).into()
+}
+
+#[marine]
+pub fn set_local_api_multiaddr(multiaddr: String) -> IpfsResult {
+    if let Ok(current) = load_local_api_multiaddr() {
+        if is_same_multiaddr(&current, &multiaddr) {
+            return eyre::Result::<()>::Ok(()).into();
+        }
+        return eyre::Result::<()>::Err(
+            ErrorKind::InvalidArgument.error("local api multiaddr can only be set once"),
+        )
+        .into();
+    }
+
+    if let Err(e) = check_admin("set local api multiaddr") {
+        return Err(e).into();
+    }
+
+    let result: eyre::Result<()> = try {
+        save_local_api_multiaddr(Multiaddr::from_str(&multiaddr).map_err(|_| {
+            ErrorKind::InvalidMultiaddr.error(format!("invalid multiaddr: {}", multiaddr))
+        })?)?
+    };
+
+    result.into()
+}
+
+#[marine]
+pub fn get_external_swarm_multiaddr() -> IpfsMultiaddrResult {
+    load_config()
+        .and_then(|config| {
+            config
+                .external_swarm_multiaddr
+                .ok_or(ErrorKind::NotConfigured.error("multiaddr is not set"))
+        })
+        .map(|m| m.to_string())
+        .into()
+}
+
+#[marine]
+pub fn set_external_swarm_multiaddr(multiaddr: String) -> IpfsResult {
+    let current = match load_config() {
+        Ok(config) => config.external_swarm_multiaddr,
+        Err(e) => return Err(e).into(),
+    };
+    if let Some(current) = current {
+        if is_same_multiaddr(&current, &multiaddr) {
+            return eyre::Result::<()>::Ok(()).into();
+        }
+        return eyre::Result::<()>::Err(
+            ErrorKind::InvalidArgument.error("external swarm multiaddr can only be set once"),
+        )
+        .into();
+    }
+
+    if let Err(e) = check_admin("set external swarm multiaddr") {
+        return Err(e).into();
+    }
+
+    let result: eyre::Result<()> = try {
+        let mut config = load_config()?;
+
+        let mut multiaddr = Multiaddr::from_str(&multiaddr).map_err(|_| {
+            ErrorKind::InvalidMultiaddr.error(format!("invalid multiaddr: {}", multiaddr))
+        })?;
+        let local_maddr = load_local_api_multiaddr()?.to_string();
+
+        let mut passed_peer_id = None;
+        match multiaddr.iter().count() {
+            3 => {
+                passed_peer_id = multiaddr.pop();
+            }
+            2 => {}
+            n => Err(ErrorKind::InvalidMultiaddr.error(format!(
+                "multiaddr should contain 2 or 3 components, {} given",
+                n
+            )))?,
+        }
+
+        let peer_id = get_peer_id(local_maddr.clone(), config.timeout_for("id"))?;
+        if passed_peer_id.is_some() && passed_peer_id != Some(peer_id.clone()) {
+            Err(ErrorKind::InvalidMultiaddr.error(format!(
+                "given peer id is different from node peer_id: given {}, actual {}",
+                passed_peer_id.unwrap(),
+                peer_id
+            )))?;
+        }
+
+        multiaddr.push(peer_id);
+        config.external_swarm_multiaddr = Some(multiaddr);
+        write_config(config)?
+    };
+
+    result.into()
+}
+
+/// Reset config to defaults, e.g. to recover from a corrupted config.
+/// Multiaddrs have to be set again afterwards.
+#[marine]
+pub fn reset_config() -> IpfsResult {
+    let result: eyre::Result<()> = try {
+        check_service_creator("reset config")?;
+        write_config(Config::default())?
+    };
+
+    result.into()
+}
+
+#[marine]
+pub fn set_timeout(timeout_sec: u64) -> IpfsResult {
+    let result: eyre::Result<()> = try {
+        check_admin("set timeout")?;
+        let mut config = load_config()?;
+        config.timeout = timeout_sec;
+        write_config(config)?
+    };
+
+    result.into()
+}
+
+/// Timeout of the operation class, the global timeout if it isn't overridden.
+#[marine]
+pub fn get_operation_timeout(operation: String) -> u64 {
+    load_config_or_default().timeout_for(&operation)
+}
+
+/// Override timeout of the operation class, one of `OPERATIONS`:
+/// connect, put, get, dag, id, repo, files, pin, name or key.
+/// Timeout 0 removes the override, so that the global timeout is used again.
+#[marine]
+pub fn set_operation_timeout(operation: String, timeout_sec: u64) -> IpfsResult {
+    let result: eyre::Result<()> = try {
+        check_admin("set operation timeout")?;
+        if !OPERATIONS.contains(&operation.as_str()) {
+            Err(ErrorKind::InvalidArgument.error(format!(
+                "unknown operation {}, expected one of {}",
+                operation,
+                OPERATIONS.join(", ")
+            )))?;
+        }
+
+        let mut config = load_config()?;
+        if timeout_sec == 0 {
+            config.operation_timeouts.remove(&operation);
+        } else {
+            config.operation_timeouts.insert(operation, timeout_sec);
+        }
+        write_config(config)?
+    };
+
+    result.into()
+}
+
+/// Get maximum size in bytes of content downloaded to the vault by `get_from`, `dag_get*`
+/// and `dag_export`, 0 means no limit.
+#[marine]
+pub fn get_max_download_size() -> u64 {
+    load_config_or_default().max_download_size
+}
+
+/// Set maximum size in bytes of content downloaded to the vault, 0 (the default) means no limit.
+/// While the limit is set, `get_from` makes an extra `ipfs files stat` call to check cumulative
+/// size of the content before downloading it and refuses objects that aren't UnixFS.
+/// Every download is measured after it completes and removed if it's over the limit.
+#[marine]
+pub fn set_max_download_size(size: u64) -> IpfsResult {
+    let result: eyre::Result<()> = try {
+        check_admin("set max download size")?;
+        let mut config = load_config()?;
+        config.max_download_size = size;
+        write_config(config)?
+    };
+
+    result.into()
+}
+
+/// List peers allowed to change service configuration. Service creator is always listed first.
+#[marine]
+pub fn list_admins() -> Vec<String> {
+    get_admins(&load_config_or_default())
+}
+
+#[marine]
+pub fn add_admin(peer_id: String) -> IpfsResult {
+    let result: eyre::Result<()> = try {
+        check_admin("add admins")?;
+        validate_peer_id(&peer_id)?;
+        let mut config = load_config()?;
+        if !get_admins(&config).contains(&peer_id) {
+            config.admins.push(peer_id);
+            write_config(config)?
+        }
+    };
+
+    result.into()
+}
+
+/// Remove admin. Service creator can't be removed.
+#[marine]
+pub fn remove_admin(peer_id: String) -> IpfsResult {
+    let result: eyre::Result<()> = try {
+        check_admin("remove admins")?;
+        if peer_id == marine_rs_sdk::get_call_parameters().service_creator_peer_id {
+            Err(ErrorKind::InvalidArgument.error("service creator can't be removed from admins"))?;
+        }
+        let mut config = load_config()?;
+        config.admins.retain(|admin| *admin != peer_id);
+        write_config(config)?
+    };
+
+    result.into()
+}
+
+#[marine]
+pub fn get_max_inline_size() -> u64 {
+    load_config_or_default().max_inline_size
+}
+
+/// Set maximum size in bytes of contents returned inline by `cat` and `get_string`.
+#[marine]
+pub fn set_max_inline_size(size: u64) -> IpfsResult {
+    let result: eyre::Result<()> = try {
+        check_admin("set max inline size")?;
+        let mut config = load_config()?;
+        config.max_inline_size = size;
+        write_config(config)?
+    };
+
+    result.into()
+}
+
+/// Service defaults of `ipfs add` parameters used by `put`, `put_string` and `put_bytes`.
+#[marine]
+pub fn get_put_options() -> IpfsPutOptions {
+    load_config_or_default().put_options.into()
+}
+
+/// Set service defaults of `ipfs add` parameters.
+#[marine]
+pub fn set_put_options(options: IpfsPutOptions) -> IpfsResult {
+    let result: eyre::Result<()> = try {
+        check_admin("set put options")?;
+        validate_put_options(&options)?;
+        let mut config = load_config()?;
+        config.put_options = options.into();
+        write_config(config)?
+    };
+
+    result.into()
+}
+
+/// Policy of retrying failed calls to IPFS made by `connect`, `put*`, `get_from`, `dag_*`.
+#[marine]
+pub fn get_retry_policy() -> IpfsRetryPolicy {
+    load_config_or_default().retry.into()
+}
+
+/// Set policy of retrying failed calls to IPFS.
+#[marine]
+pub fn set_retry_policy(policy: IpfsRetryPolicy) -> IpfsResult {
+    let result: eyre::Result<()> = try {
+        check_admin("set retry policy")?;
+        if policy.max_attempts == 0 || policy.max_attempts > MAX_RETRY_ATTEMPTS {
+            Err(ErrorKind::InvalidArgument.error(format!(
+                "max attempts must be between 1 and {}",
+                MAX_RETRY_ATTEMPTS
+            )))?;
+        }
+        if policy.base_delay_ms > MAX_TOTAL_DELAY_MS {
+            Err(ErrorKind::InvalidArgument.error(format!(
+                "base delay must not exceed {} ms",
+                MAX_TOTAL_DELAY_MS
+            )))?;
+        }
+        for kind in &policy.retryable_error_kinds {
+            if ErrorKind::parse(kind).as_str() != kind {
+                Err(ErrorKind::InvalidArgument.error(format!("unknown error kind {}", kind)))?;
+            }
+        }
+
+        let mut config = load_config()?;
+        config.retry = policy.into();
+        write_config(config)?
+    };
+
+    result.into()
+}
+
+#[marine]
+#[link(wasm_import_module = "ipfs_effector")]
+extern "C" {
+    #[link_name = "connect"]
+    pub fn ipfs_connect(
         external_multiaddr: String,
         api_multiaddr: String,
         timeout_sec: u64,
     ) -> IpfsResult;
 
+    /// List connected peers with latency and direction.
+    #[link_name = "swarm_peers"]
+    pub fn ipfs_swarm_peers(api_multiaddr: String, timeout_sec: u64) -> IpfsSwarmPeersResult;
+
+    /// Close connections to the peer.
+    #[link_name = "swarm_disconnect"]
+    pub fn ipfs_swarm_disconnect(
+        multiaddr: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsResult;
+
+    /// Add peer to the peering set.
+    #[link_name = "swarm_peering_add"]
+    pub fn ipfs_swarm_peering_add(
+        multiaddr: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsResult;
+
+    /// Remove peer from the peering set.
+    #[link_name = "swarm_peering_rm"]
+    pub fn ipfs_swarm_peering_rm(
+        peer_id: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsResult;
+
+    /// List peers in the peering set.
+    #[link_name = "swarm_peering_ls"]
+    pub fn ipfs_swarm_peering_ls(api_multiaddr: String, timeout_sec: u64) -> IpfsPeeringLsResult;
+
     /// Put provided file to ipfs, return ipfs hash of the file.
     #[link_name = "put"]
-    pub fn ipfs_put(file_path: String, api_multiaddr: String, timeout_sec: u64) -> IpfsPutResult;
+    pub fn ipfs_put(
+        file_path: String,
+        options: IpfsPutOptions,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsPutResult;
+
+    /// Read contents of the file from ipfs by hash.
+    #[link_name = "cat"]
+    pub fn ipfs_cat(
+        hash: String,
+        max_size: u64,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsCatResult;
+
+    /// Read a slice of the file.
+    #[link_name = "cat_range"]
+    pub fn ipfs_cat_range(
+        hash: String,
+        offset: u64,
+        length: u64,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsCatResult;
+
+    /// Save a slice of the file to the vault.
+    #[link_name = "get_range"]
+    pub fn ipfs_get_range(
+        hash: String,
+        offset: u64,
+        length: u64,
+        file_path: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsResult;
+
+    /// Put provided directory to ipfs recursively, return ipfs hash of its root.
+    #[link_name = "put_directory"]
+    pub fn ipfs_put_directory(
+        dir_path: String,
+        wrap_with_directory: bool,
+        hidden: bool,
+        ignore: Vec<String>,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsPutResult;
 
     /// DAG put provided file to ipfs, return ipfs hash of the file.
     #[link_name = "dag_put"]
-    pub fn ipfs_dag_put(file_path: String, api_multiaddr: String, timeout_sec: u64) -> IpfsPutResult;
+    pub fn ipfs_dag_put(
+        file_path: String,
+        input_codec: String,
+        store_codec: String,
+        hash: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsPutResult;
 
     /// Get file from ipfs by hash.
     #[link_name = "get"]
@@ -395,15 +2311,208 @@ extern "C" {
         timeout_sec: u64,
     ) -> IpfsResult;
 
-    /// Get file from ipfs by hash using DAG get.
+    /// DAG get node by hash and IPLD path, save it to the file encoded with output codec.
     #[link_name = "dag_get"]
     pub fn ipfs_dag_get(
+        hash: String,
+        path: String,
+        output_codec: String,
+        file_path: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsResult;
+
+    /// Export DAG as CAR file.
+    #[link_name = "dag_export"]
+    pub fn ipfs_dag_export(
         hash: String,
         file_path: String,
         api_multiaddr: String,
         timeout_sec: u64,
     ) -> IpfsResult;
 
+    /// Import CAR file, return its roots.
+    #[link_name = "dag_import"]
+    pub fn ipfs_dag_import(
+        file_path: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsDagImportResult;
+
+    /// Get size and type of the object.
+    #[link_name = "stat"]
+    pub fn ipfs_stat(hash: String, api_multiaddr: String, timeout_sec: u64) -> IpfsStatResult;
+
+    /// Get size of any DAG.
+    #[link_name = "dag_stat"]
+    pub fn ipfs_dag_stat(hash: String, api_multiaddr: String, timeout_sec: u64) -> IpfsStatResult;
+
     #[link_name = "get_peer_id"]
     pub fn ipfs_get_peer_id(local_multiaddr: String, timeout_sec: u64) -> IpfsGetPeerIdResult;
+
+    /// List links of the directory.
+    #[link_name = "ls"]
+    pub fn ipfs_ls(
+        hash: String,
+        resolve_type: bool,
+        max_entries: u64,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsLsResult;
+
+    /// Create MFS directory.
+    #[link_name = "files_mkdir"]
+    pub fn ipfs_files_mkdir(
+        path: String,
+        parents: bool,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsResult;
+
+    /// Copy to MFS.
+    #[link_name = "files_cp"]
+    pub fn ipfs_files_cp(
+        source: String,
+        dest: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsResult;
+
+    /// Write vault file to MFS.
+    #[link_name = "files_write"]
+    pub fn ipfs_files_write(
+        path: String,
+        file_path: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsResult;
+
+    /// Read MFS file.
+    #[link_name = "files_read"]
+    pub fn ipfs_files_read(
+        path: String,
+        offset: u64,
+        count: u64,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsCatResult;
+
+    /// List MFS directory.
+    #[link_name = "files_ls"]
+    pub fn ipfs_files_ls(path: String, api_multiaddr: String, timeout_sec: u64) -> IpfsLsResult;
+
+    /// Remove from MFS.
+    #[link_name = "files_rm"]
+    pub fn ipfs_files_rm(
+        path: String,
+        recursive: bool,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsResult;
+
+    /// Move within MFS.
+    #[link_name = "files_mv"]
+    pub fn ipfs_files_mv(
+        source: String,
+        dest: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsResult;
+
+    /// Get CID, size and type of MFS path.
+    #[link_name = "files_stat"]
+    pub fn ipfs_files_stat(path: String, api_multiaddr: String, timeout_sec: u64)
+        -> IpfsStatResult;
+
+    /// Flush MFS path and return its CID.
+    #[link_name = "files_flush"]
+    pub fn ipfs_files_flush(path: String, api_multiaddr: String, timeout_sec: u64)
+        -> IpfsCidResult;
+
+    /// Pin object to local storage.
+    #[link_name = "pin_add"]
+    pub fn ipfs_pin_add(
+        hash: String,
+        recursive: bool,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsResult;
+
+    /// Remove pinned object from local storage.
+    #[link_name = "pin_rm"]
+    pub fn ipfs_pin_rm(
+        hash: String,
+        recursive: bool,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsResult;
+
+    /// List pinned objects of the given type.
+    #[link_name = "pin_ls"]
+    pub fn ipfs_pin_ls(
+        hash: String,
+        pin_type: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsPinLsResult;
+
+    /// Verify that recursive pins are complete.
+    #[link_name = "pin_verify"]
+    pub fn ipfs_pin_verify(api_multiaddr: String, timeout_sec: u64) -> IpfsPinVerifyResult;
+
+    /// Report local repo size and number of objects.
+    #[link_name = "repo_stat"]
+    pub fn ipfs_repo_stat(api_multiaddr: String, timeout_sec: u64) -> IpfsRepoStatResult;
+
+    /// Remove unpinned blocks from local repo.
+    #[link_name = "repo_gc"]
+    pub fn ipfs_repo_gc(api_multiaddr: String, timeout_sec: u64) -> IpfsRepoGcResult;
+
+    /// Verify integrity of blocks in local repo.
+    #[link_name = "repo_verify"]
+    pub fn ipfs_repo_verify(api_multiaddr: String, timeout_sec: u64) -> IpfsRepoVerifyResult;
+
+    /// Publish IPNS record for the key.
+    #[link_name = "name_publish"]
+    pub fn ipfs_name_publish(
+        hash: String,
+        key: String,
+        lifetime: String,
+        ttl: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsNamePublishResult;
+
+    /// Resolve IPNS name to path.
+    #[link_name = "name_resolve"]
+    pub fn ipfs_name_resolve(
+        name: String,
+        recursive: bool,
+        nocache: bool,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsNameResolveResult;
+
+    #[link_name = "key_gen"]
+    pub fn ipfs_key_gen(
+        name: String,
+        key_type: String,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsKeyResult;
+
+    #[link_name = "key_list"]
+    pub fn ipfs_key_list(api_multiaddr: String, timeout_sec: u64) -> IpfsKeyListResult;
+
+    #[link_name = "key_rm"]
+    pub fn ipfs_key_rm(name: String, api_multiaddr: String, timeout_sec: u64) -> IpfsKeyListResult;
+
+    #[link_name = "key_rename"]
+    pub fn ipfs_key_rename(
+        old_name: String,
+        new_name: String,
+        force: bool,
+        api_multiaddr: String,
+        timeout_sec: u64,
+    ) -> IpfsKeyResult;
 }
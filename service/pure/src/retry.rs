@@ -0,0 +1,88 @@
+/*
+ * Copyright 2021 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Retrying of effector calls according to the `RetryPolicy` from config.
+
+use crate::config::RetryPolicy;
+
+use std::time::Duration;
+use types::{IpfsDagImportResult, IpfsPutResult, IpfsResult};
+
+/// Total time spent waiting between attempts of a single call. Sleeping blocks the service,
+/// so the budget is kept well below the default particle TTL of 7 seconds.
+pub const MAX_TOTAL_DELAY_MS: u64 = 3_000;
+
+/// Result of an effector call that can be retried.
+pub trait Attempt {
+    fn failed_with(&self) -> Option<&str>;
+    fn set_attempts(&mut self, attempts: u32);
+}
+
+macro_rules! impl_attempt {
+    ($($result:ty),*) => {
+        $(
+            impl Attempt for $result {
+                fn failed_with(&self) -> Option<&str> {
+                    if self.success {
+                        None
+                    } else {
+                        Some(&self.error_kind)
+                    }
+                }
+
+                fn set_attempts(&mut self, attempts: u32) {
+                    self.attempts = attempts;
+                }
+            }
+        )*
+    };
+}
+
+impl_attempt!(IpfsResult, IpfsPutResult, IpfsDagImportResult);
+
+/// Call `f` with the attempt number until it succeeds, fails with an error kind
+/// that isn't retryable or runs out of attempts. Delay doubles after every attempt,
+/// retrying stops early once the next delay would exceed `MAX_TOTAL_DELAY_MS` in total.
+pub fn with_retry<T: Attempt>(policy: &RetryPolicy, mut f: impl FnMut(u32) -> T) -> T {
+    let max_attempts = std::cmp::max(1, policy.max_attempts);
+    let mut delay_ms = policy.base_delay_ms;
+    let mut total_delay_ms = 0u64;
+    let mut attempt = 1;
+    loop {
+        let mut result = f(attempt);
+        let retryable = match result.failed_with() {
+            Some(kind) => policy.retryable_error_kinds.iter().any(|k| k == kind),
+            None => false,
+        };
+
+        let out_of_time = total_delay_ms.saturating_add(delay_ms) > MAX_TOTAL_DELAY_MS;
+        if !retryable || attempt >= max_attempts || out_of_time {
+            result.set_attempts(attempt);
+            return result;
+        }
+
+        log::warn!(
+            "attempt {} of {} failed, retrying in {} ms",
+            attempt,
+            max_attempts,
+            delay_ms
+        );
+        std::thread::sleep(Duration::from_millis(delay_ms));
+        total_delay_ms += delay_ms;
+        delay_ms = delay_ms.saturating_mul(2);
+        attempt += 1;
+    }
+}
@@ -0,0 +1,235 @@
+/*
+ * Copyright 2021 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Local UnixFS importer that computes file CIDs the same way `ipfs add` does
+//! with the balanced layout and fixed-size chunker, without storing any blocks.
+
+use crate::cid::{write_varint, Cid, CODEC_DAG_PB, CODEC_RAW, MULTIHASH_SHA2_256};
+
+use eyre::{Result, WrapErr};
+use multiaddr::multihash::Multihash;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+
+pub const DEFAULT_CHUNK_SIZE: usize = 256 * 1024;
+/// Kubo's default maximum number of links per node for balanced layout.
+const MAX_LINKS: usize = 174;
+/// Kubo refuses to create blocks larger than 1MiB.
+const MAX_CHUNK_SIZE: usize = 1024 * 1024;
+const UNIXFS_FILE_TYPE: u64 = 2;
+
+pub struct ImportParams {
+    pub cid_version: u64,
+    pub hash: u64,
+    pub chunk_size: usize,
+    pub raw_leaves: bool,
+}
+
+/// Parse `ipfs add --chunker` value. Only fixed-size chunker is supported.
+pub fn parse_chunker(chunker: &str) -> Result<usize> {
+    if chunker.is_empty() || chunker == "default" {
+        return Ok(DEFAULT_CHUNK_SIZE);
+    }
+
+    let size = chunker
+        .strip_prefix("size-")
+        .ok_or(eyre::eyre!(
+            "unsupported chunker: {}, only size-<bytes> is supported",
+            chunker
+        ))?
+        .parse::<usize>()
+        .wrap_err(format!("invalid chunker: {}", chunker))?;
+
+    if size == 0 || size > MAX_CHUNK_SIZE {
+        Err(eyre::eyre!(
+            "chunk size must be between 1 and {}, {} given",
+            MAX_CHUNK_SIZE,
+            size
+        ))?;
+    }
+
+    Ok(size)
+}
+
+/// Compute root CID of the UnixFS file read from `reader`.
+pub fn file_cid<R: Read>(reader: R, params: &ImportParams) -> Result<Cid> {
+    if params.hash != MULTIHASH_SHA2_256 {
+        Err(eyre::eyre!("only sha2-256 is supported for local hashing"))?;
+    }
+
+    let mut builder = DagBuilder::new(reader, params)?;
+    Ok(builder.layout()?.cid)
+}
+
+struct Node {
+    cid: Cid,
+    /// Cumulative size of the node and all its children, i.e. link `Tsize`.
+    size: u64,
+    file_size: u64,
+}
+
+struct DagBuilder<'p, R: Read> {
+    reader: R,
+    params: &'p ImportParams,
+    next_chunk: Option<Vec<u8>>,
+}
+
+impl<'p, R: Read> DagBuilder<'p, R> {
+    fn new(reader: R, params: &'p ImportParams) -> Result<Self> {
+        let mut builder = Self {
+            reader,
+            params,
+            next_chunk: None,
+        };
+        builder.prepare_next()?;
+        Ok(builder)
+    }
+
+    /// Read next chunk ahead of time, so that `done` knows if there is more data.
+    fn prepare_next(&mut self) -> Result<()> {
+        let mut chunk = Vec::with_capacity(self.params.chunk_size);
+        (&mut self.reader)
+            .take(self.params.chunk_size as u64)
+            .read_to_end(&mut chunk)
+            .wrap_err("failed to read file")?;
+        self.next_chunk = if chunk.is_empty() { None } else { Some(chunk) };
+        Ok(())
+    }
+
+    fn done(&self) -> bool {
+        self.next_chunk.is_none()
+    }
+
+    fn layout(&mut self) -> Result<Node> {
+        if self.done() {
+            return self.leaf(vec![]);
+        }
+
+        let mut root = self.next_leaf()?;
+        let mut depth = 1;
+        while !self.done() {
+            root = self.fill(vec![root], depth)?;
+            depth += 1;
+        }
+
+        Ok(root)
+    }
+
+    fn fill(&mut self, mut children: Vec<Node>, depth: usize) -> Result<Node> {
+        while children.len() < MAX_LINKS && !self.done() {
+            let child = if depth == 1 {
+                self.next_leaf()?
+            } else {
+                self.fill(vec![], depth - 1)?
+            };
+            children.push(child);
+        }
+
+        let file_size = children.iter().map(|c| c.file_size).sum();
+        let blocksizes: Vec<u64> = children.iter().map(|c| c.file_size).collect();
+        let data = encode_unixfs_file(None, file_size, &blocksizes);
+        let links: Vec<(&Cid, u64)> = children.iter().map(|c| (&c.cid, c.size)).collect();
+        let block = encode_dag_pb(&links, Some(&data));
+
+        Ok(Node {
+            cid: self.cid(CODEC_DAG_PB, &block)?,
+            size: block.len() as u64 + children.iter().map(|c| c.size).sum::<u64>(),
+            file_size,
+        })
+    }
+
+    fn next_leaf(&mut self) -> Result<Node> {
+        let chunk = self.next_chunk.take().unwrap_or_default();
+        self.prepare_next()?;
+        self.leaf(chunk)
+    }
+
+    fn leaf(&self, chunk: Vec<u8>) -> Result<Node> {
+        let file_size = chunk.len() as u64;
+        if self.params.raw_leaves {
+            return Ok(Node {
+                cid: self.cid(CODEC_RAW, &chunk)?,
+                size: file_size,
+                file_size,
+            });
+        }
+
+        let data = if chunk.is_empty() {
+            encode_unixfs_file(None, 0, &[])
+        } else {
+            encode_unixfs_file(Some(&chunk), file_size, &[])
+        };
+        let block = encode_dag_pb(&[], Some(&data));
+
+        Ok(Node {
+            cid: self.cid(CODEC_DAG_PB, &block)?,
+            size: block.len() as u64,
+            file_size,
+        })
+    }
+
+    fn cid(&self, codec: u64, block: &[u8]) -> Result<Cid> {
+        let hash = Multihash::wrap(MULTIHASH_SHA2_256, &Sha256::digest(block))
+            .wrap_err("failed to build multihash")?;
+        // raw blocks can't be addressed by CIDv0, so ipfs add upgrades them to CIDv1
+        if self.params.cid_version == 0 && codec == CODEC_DAG_PB {
+            Cid::new_v0(hash)
+        } else {
+            Ok(Cid::new_v1(codec, hash))
+        }
+    }
+}
+
+fn write_bytes_field(field: u64, bytes: &[u8], buf: &mut Vec<u8>) {
+    write_varint((field << 3) | 2, buf);
+    write_varint(bytes.len() as u64, buf);
+    buf.extend_from_slice(bytes);
+}
+
+fn write_varint_field(field: u64, value: u64, buf: &mut Vec<u8>) {
+    write_varint(field << 3, buf);
+    write_varint(value, buf);
+}
+
+/// Encode UnixFS `Data` protobuf message of File type.
+fn encode_unixfs_file(data: Option<&[u8]>, file_size: u64, blocksizes: &[u64]) -> Vec<u8> {
+    let mut buf = vec![];
+    write_varint_field(1, UNIXFS_FILE_TYPE, &mut buf);
+    if let Some(data) = data {
+        write_bytes_field(2, data, &mut buf);
+    }
+    write_varint_field(3, file_size, &mut buf);
+    for size in blocksizes {
+        write_varint_field(4, *size, &mut buf);
+    }
+    buf
+}
+
+/// Encode `PBNode` in canonical dag-pb form: links first, then data.
+fn encode_dag_pb(links: &[(&Cid, u64)], data: Option<&[u8]>) -> Vec<u8> {
+    let mut buf = vec![];
+    for (cid, size) in links {
+        let mut link = vec![];
+        write_bytes_field(1, &cid.to_bytes(), &mut link);
+        write_bytes_field(2, &[], &mut link);
+        write_varint_field(3, *size, &mut link);
+        write_bytes_field(2, &link, &mut buf);
+    }
+    if let Some(data) = data {
+        write_bytes_field(1, data, &mut buf);
+    }
+    buf
+}
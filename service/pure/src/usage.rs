@@ -0,0 +1,141 @@
+/*
+ * Copyright 2021 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Accounting of bytes the service writes to particle vaults.
+//!
+//! Usage is stored in `/storage` per particle along with its init peer. Vaults are removed by
+//! the node when particles expire, so records older than `RECORD_TTL_SEC` are dropped.
+
+use eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use types::IpfsVaultUsage;
+
+const USAGE_FILE_PATH: &str = "/storage/vault_usage.toml";
+const RECORD_TTL_SEC: u64 = 60 * 60;
+
+#[derive(Deserialize, Serialize, Default)]
+struct Usage {
+    #[serde(default)]
+    particles: BTreeMap<String, ParticleUsage>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct ParticleUsage {
+    init_peer_id: String,
+    bytes: u64,
+    updated_at: u64,
+}
+
+/// Account everything at `path`, a file or a directory, to the current particle.
+/// Accounting never fails the call that wrote the data, errors are only logged.
+pub fn record_vault_write(path: &str) {
+    let result: eyre::Result<()> = try {
+        let bytes = disk_usage(Path::new(path))?;
+        let call_parameters = marine_rs_sdk::get_call_parameters();
+        let now = now_sec();
+
+        let mut usage = load_usage()?;
+        let record = usage
+            .particles
+            .entry(call_parameters.particle_id)
+            .or_insert(ParticleUsage {
+                init_peer_id: call_parameters.init_peer_id,
+                bytes: 0,
+                updated_at: now,
+            });
+        record.bytes = record.bytes.saturating_add(bytes);
+        record.updated_at = now;
+        write_usage(usage)?;
+    };
+
+    if let Err(e) = result {
+        log::warn!("failed to record vault usage of {}: {:?}", path, e);
+    }
+}
+
+/// Usage of the current particle, its init peer and all particles.
+pub fn vault_usage() -> eyre::Result<IpfsVaultUsage> {
+    let call_parameters = marine_rs_sdk::get_call_parameters();
+    let usage = load_usage()?;
+
+    let particle_bytes = usage
+        .particles
+        .get(&call_parameters.particle_id)
+        .map(|record| record.bytes)
+        .unwrap_or(0);
+    let peer_bytes = usage
+        .particles
+        .values()
+        .filter(|record| record.init_peer_id == call_parameters.init_peer_id)
+        .map(|record| record.bytes)
+        .sum();
+    let total_bytes = usage.particles.values().map(|record| record.bytes).sum();
+
+    Ok(IpfsVaultUsage {
+        particle_bytes,
+        peer_bytes,
+        total_bytes,
+    })
+}
+
+fn load_usage() -> eyre::Result<Usage> {
+    if !Path::new(USAGE_FILE_PATH).exists() {
+        return Ok(Usage::default());
+    }
+
+    let content = fs::read_to_string(USAGE_FILE_PATH)
+        .wrap_err(format!("failed to read {}", USAGE_FILE_PATH))?;
+    let mut usage: Usage =
+        toml::from_str(&content).wrap_err(format!("failed to parse {}", USAGE_FILE_PATH))?;
+
+    let now = now_sec();
+    usage
+        .particles
+        .retain(|_, record| now.saturating_sub(record.updated_at) < RECORD_TTL_SEC);
+    Ok(usage)
+}
+
+fn write_usage(usage: Usage) -> eyre::Result<()> {
+    let tmp_path = format!("{}.tmp", USAGE_FILE_PATH);
+    fs::write(&tmp_path, toml::to_string(&usage)?)?;
+    fs::rename(&tmp_path, USAGE_FILE_PATH)?;
+    Ok(())
+}
+
+/// Size of the file or total size of files in the directory at `path`, symlinks aren't followed.
+pub fn disk_usage(path: &Path) -> eyre::Result<u64> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        size += disk_usage(&entry?.path())?;
+    }
+    Ok(size)
+}
+
+fn now_sec() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
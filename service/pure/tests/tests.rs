@@ -19,14 +19,79 @@ mod tests {
     marine_rs_sdk_test::include_test_env!("/marine_test_env.rs");
     use marine_test_env::ipfs::ServiceInterface;
 
+    static STORAGE: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    /// All services share the storage directory mapped in Config.toml, so tests using it
+    /// run one at a time, each starting with an empty storage, i.e. the default config.
+    fn new_service() -> (std::sync::MutexGuard<'static, ()>, ServiceInterface) {
+        let guard = STORAGE.lock().unwrap_or_else(|e| e.into_inner());
+        for entry in std::fs::read_dir("tests/storage").unwrap() {
+            let path = entry.unwrap().path();
+            if path.file_name().unwrap() != ".gitignore" {
+                std::fs::remove_file(path).unwrap();
+            }
+        }
+        (guard, ServiceInterface::new())
+    }
+
     fn set_default_local_api_multiaddr(ipfs: &mut ServiceInterface) {
         let result = ipfs.set_local_api_multiaddr("/ip4/127.0.0.1/tcp/9992".to_string());
         assert!(result.success);
     }
 
+    /// Write `contents` to the vault directory mapped in tests, return its path inside the service.
+    fn write_vault_file(name: &str, contents: &[u8]) -> String {
+        std::fs::create_dir_all("tests/vault").unwrap();
+        std::fs::write(format!("tests/vault/{}", name), contents).unwrap();
+        format!("/tmp/vault/{}", name)
+    }
+
+    fn non_admin_call_parameters() -> marine_rs_sdk::CallParameters {
+        marine_rs_sdk::CallParameters {
+            init_peer_id: "init_peer_id".to_string(),
+            service_id: "service_id".to_string(),
+            service_creator_peer_id: "service_creator_peer_id".to_string(),
+            host_id: "host_id".to_string(),
+            particle_id: "particle_id".to_string(),
+            tetraplets: vec![],
+        }
+    }
+
+    const CAR_HELLO_CID: &str = "bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq";
+    const CAR_HELLO_CID_HEX: &str =
+        "015512202cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+    const CAR_WORLD_CID_HEX: &str =
+        "01551220486ea46224d1bb4fb680f34f7c9ad96a8f24ec88be73ea8e5a6c65260e9cb8a7";
+
+    fn unhex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Build CARv1 bytes with a single root; all lengths must fit in a one-byte varint.
+    fn car_v1(root_hex: &str, blocks: &[(&str, &[u8])]) -> Vec<u8> {
+        // {"roots": [tag(42, bytes(0x00 ++ cid))], "version": 1}
+        let mut header = unhex("a265726f6f747381d82a582500");
+        header.extend(unhex(root_hex));
+        header.extend(unhex("6776657273696f6e01"));
+
+        let mut car = vec![header.len() as u8];
+        car.extend(header);
+        for (cid_hex, data) in blocks {
+            let cid = unhex(cid_hex);
+            assert!(cid.len() + data.len() < 0x80);
+            car.push((cid.len() + data.len()) as u8);
+            car.extend(cid);
+            car.extend(*data);
+        }
+        car
+    }
+
     #[test]
     fn invalid_multiaddr() {
-        let mut ipfs = ServiceInterface::new();
+        let (_storage, mut ipfs) = new_service();
         let invalid_multiaddr = "invalid_multiaddr".to_string();
         let result = ipfs.set_local_api_multiaddr(invalid_multiaddr.clone());
         assert!(!result.success);
@@ -36,9 +101,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn corrupted_config() {
+        let (_storage, mut ipfs) = new_service();
+        std::fs::write("tests/storage/config.toml", "version = ").unwrap();
+
+        let result = ipfs.set_timeout(10);
+        assert!(!result.success);
+        assert!(
+            result.error.contains("call reset_config"),
+            "{}",
+            result.error
+        );
+
+        let result = ipfs.reset_config();
+        assert!(result.success, "{}", result.error);
+        let result = ipfs.set_timeout(10);
+        assert!(result.success, "{}", result.error);
+    }
+
     #[test]
     fn set_get_external_api_multiaddr() {
-        let mut ipfs = ServiceInterface::new();
+        let (_storage, mut ipfs) = new_service();
         set_default_local_api_multiaddr(&mut ipfs);
         let multiaddr = "/ip4/127.0.0.1/tcp/9992";
         let result = ipfs.set_external_api_multiaddr(multiaddr.to_string());
@@ -57,7 +141,7 @@ mod tests {
 
     #[test]
     fn set_get_external_swarm_multiaddr() {
-        let mut ipfs = ServiceInterface::new();
+        let (_storage, mut ipfs) = new_service();
         set_default_local_api_multiaddr(&mut ipfs);
         let multiaddr = "/ip4/127.0.0.1/tcp/9992";
         let result = ipfs.set_external_swarm_multiaddr(multiaddr.to_string());
@@ -73,4 +157,468 @@ mod tests {
         assert!(result.success);
         assert_eq!(format!("{}/p2p/{}", multiaddr, peer_id), result.multiaddr);
     }
+
+    #[test]
+    fn cid_info_and_conversion() {
+        let (_storage, mut ipfs) = new_service();
+        let cid_v0 = "QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR1n";
+        let cid_v1 = "bafybeihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku";
+
+        let info = ipfs.cid_info(cid_v0.to_string());
+        assert!(info.success);
+        assert_eq!(0, info.version);
+        assert_eq!("dag-pb", info.codec);
+        assert_eq!("sha2-256", info.multihash);
+        assert_eq!("base58btc", info.multibase);
+
+        let result = ipfs.cid_to_v1(cid_v0.to_string(), "".to_string());
+        assert!(result.success);
+        assert_eq!(cid_v1, result.cid);
+
+        let result = ipfs.cid_to_v0(cid_v1.to_string());
+        assert!(result.success);
+        assert_eq!(cid_v0, result.cid);
+    }
+
+    #[test]
+    fn get_from_invalid_cid() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.get_from("--help".to_string(), "/ip4/127.0.0.1/tcp/5001".to_string());
+        assert!(!result.success);
+        assert!(result.error.starts_with("invalid cid --help"));
+        assert_eq!("InvalidCid", result.error_kind);
+    }
+
+    #[test]
+    fn reset_config_by_non_creator() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.reset_config_cp(non_admin_call_parameters());
+        assert!(!result.success);
+        assert_eq!(
+            "permission denied: peer init_peer_id is not allowed to reset config",
+            result.error
+        );
+    }
+
+    #[test]
+    fn set_same_local_api_multiaddr_twice() {
+        let (_storage, mut ipfs) = new_service();
+        set_default_local_api_multiaddr(&mut ipfs);
+        set_default_local_api_multiaddr(&mut ipfs);
+
+        let result = ipfs.set_local_api_multiaddr("/ip4/127.0.0.1/tcp/9993".to_string());
+        assert!(!result.success);
+    }
+
+    #[test]
+    fn set_timeout_by_non_admin() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.set_timeout_cp(10, non_admin_call_parameters());
+        assert!(!result.success);
+        assert!(result.error.starts_with("permission denied"));
+        assert_eq!("PermissionDenied", result.error_kind);
+    }
+
+    #[test]
+    fn put_with_options_v0_non_default_hash() {
+        let (_storage, mut ipfs) = new_service();
+        let mut options = ipfs.get_put_options();
+        assert_eq!(0, options.cid_version);
+        options.hash = "blake2b-256".to_string();
+
+        let result = ipfs.put_with_options("tmp".to_string(), options);
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+    }
+
+    #[test]
+    fn dag_put_unsupported_codec() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.dag_put_with_options(
+            "tmp".to_string(),
+            "dag-json".to_string(),
+            "protobuf".to_string(),
+            "sha2-256".to_string(),
+        );
+        assert!(!result.success);
+        assert!(result.error.starts_with("unsupported dag codec protobuf"));
+        assert_eq!("InvalidArgument", result.error_kind);
+    }
+
+    #[test]
+    fn car_info_missing_file() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.car_info("/tmp/missing.car".to_string());
+        assert!(!result.success);
+        assert_eq!("NotFound", result.error_kind);
+    }
+
+    #[test]
+    fn car_info_v1_and_v2() {
+        let (_storage, mut ipfs) = new_service();
+        let v1 = car_v1(
+            CAR_HELLO_CID_HEX,
+            &[(CAR_HELLO_CID_HEX, b"hello"), (CAR_WORLD_CID_HEX, b"world")],
+        );
+
+        let result = ipfs.car_info(write_vault_file("car_v1", &v1));
+        assert!(result.success, "{}", result.error);
+        assert_eq!(1, result.version);
+        assert_eq!(vec![CAR_HELLO_CID.to_string()], result.roots);
+        assert_eq!(2, result.blocks.len());
+        assert_eq!(CAR_HELLO_CID, result.blocks[0]);
+
+        // CARv2 pragma, then header: characteristics, data offset 51, data size, index offset 0
+        let mut v2 = unhex("0aa16776657273696f6e02");
+        v2.extend(&[0u8; 16]);
+        v2.extend(&51u64.to_le_bytes());
+        v2.extend(&(v1.len() as u64).to_le_bytes());
+        v2.extend(&0u64.to_le_bytes());
+        v2.extend(&v1);
+
+        let result = ipfs.car_info(write_vault_file("car_v2", &v2));
+        assert!(result.success, "{}", result.error);
+        assert_eq!(2, result.version);
+        assert_eq!(vec![CAR_HELLO_CID.to_string()], result.roots);
+        assert_eq!(2, result.blocks.len());
+    }
+
+    #[test]
+    fn car_truncated() {
+        let (_storage, mut ipfs) = new_service();
+        let v1 = car_v1(
+            CAR_HELLO_CID_HEX,
+            &[(CAR_HELLO_CID_HEX, b"hello"), (CAR_WORLD_CID_HEX, b"world")],
+        );
+
+        let section = write_vault_file("car_truncated_section", &v1[..v1.len() - 3]);
+        let result = ipfs.car_info(section.clone());
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+        let result = ipfs.car_verify(section);
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+
+        // a varint with the continuation bit set and nothing after it
+        let mut varint = v1.clone();
+        varint.push(0x80);
+        let result = ipfs.car_info(write_vault_file("car_truncated_varint", &varint));
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+    }
+
+    #[test]
+    fn car_verify_blocks_and_roots() {
+        let (_storage, mut ipfs) = new_service();
+        let v1 = car_v1(
+            CAR_HELLO_CID_HEX,
+            &[(CAR_HELLO_CID_HEX, b"hello"), (CAR_WORLD_CID_HEX, b"world")],
+        );
+        let result = ipfs.car_verify(write_vault_file("car_verify_v1", &v1));
+        assert!(result.success, "{}", result.error);
+        assert_eq!(vec![CAR_HELLO_CID.to_string()], result.roots);
+        assert_eq!(2, result.block_count);
+        assert_eq!(10, result.total_size);
+
+        let corrupt = car_v1(CAR_HELLO_CID_HEX, &[(CAR_HELLO_CID_HEX, b"hellO")]);
+        let result = ipfs.car_verify(write_vault_file("car_verify_corrupt", &corrupt));
+        assert!(!result.success);
+        assert_eq!("IntegrityCheckFailed", result.error_kind);
+
+        let missing_root = car_v1(CAR_WORLD_CID_HEX, &[(CAR_HELLO_CID_HEX, b"hello")]);
+        let result = ipfs.car_verify(write_vault_file("car_verify_missing_root", &missing_root));
+        assert!(!result.success);
+        assert_eq!("IntegrityCheckFailed", result.error_kind);
+        assert!(result.error.contains("is missing"), "{}", result.error);
+    }
+
+    #[test]
+    fn get_from_any_unknown_strategy() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.get_from_any(
+            "QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR1n".to_string(),
+            vec!["/ip4/127.0.0.1/tcp/5001".to_string()],
+            "race".to_string(),
+        );
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+        assert_eq!("unknown strategy race, expected failover", result.error);
+        assert!(result.source_errors.is_empty());
+    }
+
+    #[test]
+    fn set_retry_policy_unknown_error_kind() {
+        let (_storage, mut ipfs) = new_service();
+        let mut policy = ipfs.get_retry_policy();
+        policy.retryable_error_kinds = vec!["Timeout".to_string(), "Flaky".to_string()];
+
+        let result = ipfs.set_retry_policy(policy);
+        assert!(!result.success);
+        assert_eq!("unknown error kind Flaky", result.error);
+        assert_eq!("InvalidArgument", result.error_kind);
+    }
+
+    #[test]
+    fn set_retry_policy_delay_over_budget() {
+        let (_storage, mut ipfs) = new_service();
+        let mut policy = ipfs.get_retry_policy();
+        policy.max_attempts = 3;
+        policy.base_delay_ms = 30_000;
+
+        let result = ipfs.set_retry_policy(policy);
+        assert!(!result.success);
+        assert_eq!("base delay must not exceed 3000 ms", result.error);
+        assert_eq!("InvalidArgument", result.error_kind);
+    }
+
+    #[test]
+    fn set_operation_timeout() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.set_operation_timeout("download".to_string(), 60);
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+
+        let result = ipfs.set_operation_timeout("get".to_string(), 60);
+        assert!(result.success);
+        assert_eq!(60, ipfs.get_operation_timeout("get".to_string()));
+
+        let result = ipfs.set_operation_timeout("get".to_string(), 0);
+        assert!(result.success);
+        assert_ne!(60, ipfs.get_operation_timeout("get".to_string()));
+
+        let result = ipfs.set_operation_timeout("pin".to_string(), 30);
+        assert!(result.success);
+        assert_eq!(30, ipfs.get_operation_timeout("pin".to_string()));
+    }
+
+    #[test]
+    fn vault_usage_of_new_particle() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.vault_usage();
+        assert!(result.success);
+        assert_eq!(0, result.particle_bytes);
+    }
+
+    #[test]
+    fn swarm_disconnect_by_non_admin() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.swarm_disconnect_cp(
+            "/ip4/127.0.0.1/tcp/4001".to_string(),
+            non_admin_call_parameters(),
+        );
+        assert!(!result.success);
+        assert_eq!("PermissionDenied", result.error_kind);
+    }
+
+    #[test]
+    fn swarm_peering_add_without_peer_id() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.swarm_peering_add("/ip4/127.0.0.1/tcp/4001".to_string());
+        assert!(!result.success);
+        assert_eq!("InvalidMultiaddr", result.error_kind);
+    }
+
+    #[test]
+    fn swarm_peering_rm_invalid_peer_id() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.swarm_peering_rm("--all".to_string());
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+        assert_eq!("invalid peer id --all", result.error);
+    }
+
+    #[test]
+    fn repo_gc_by_non_admin() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.repo_gc_cp(non_admin_call_parameters());
+        assert!(!result.success);
+        assert_eq!("PermissionDenied", result.error_kind);
+        assert_eq!(0, result.removed);
+    }
+
+    #[test]
+    fn stat_from_invalid_cid() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.stat_from(
+            "invalid_cid".to_string(),
+            "/ip4/127.0.0.1/tcp/5001".to_string(),
+        );
+        assert!(!result.success);
+        assert_eq!("InvalidCid", result.error_kind);
+    }
+
+    #[test]
+    fn ls_from_invalid_multiaddr() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.ls_from(
+            "QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR1n".to_string(),
+            "invalid_multiaddr".to_string(),
+        );
+        assert!(!result.success);
+        assert_eq!("InvalidMultiaddr", result.error_kind);
+    }
+
+    #[test]
+    fn cat_range_zero_length() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.cat_range(
+            "QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR1n".to_string(),
+            10,
+            0,
+        );
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+    }
+
+    #[test]
+    fn files_paths_outside_root() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.files_rm("/".to_string(), true);
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+
+        let result = ipfs.files_mv("a".to_string(), "../other_service/a".to_string());
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+    }
+
+    #[test]
+    fn files_mutations_by_non_admin() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.files_mkdir_cp("dir".to_string(), non_admin_call_parameters());
+        assert!(!result.success);
+        assert_eq!("PermissionDenied", result.error_kind);
+
+        let result = ipfs.files_rm_cp("dir".to_string(), true, non_admin_call_parameters());
+        assert!(!result.success);
+        assert_eq!("PermissionDenied", result.error_kind);
+    }
+
+    #[test]
+    fn pin_add_invalid_cid() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.pin_add("--help".to_string(), true);
+        assert!(!result.success);
+        assert_eq!("InvalidCid", result.error_kind);
+    }
+
+    #[test]
+    fn pin_rm_by_non_admin() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.pin_rm_cp(
+            "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+            true,
+            non_admin_call_parameters(),
+        );
+        assert!(!result.success);
+        assert_eq!("PermissionDenied", result.error_kind);
+    }
+
+    #[test]
+    fn key_gen_and_name_publish_by_non_admin() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.key_gen_cp(
+            "key".to_string(),
+            "ed25519".to_string(),
+            non_admin_call_parameters(),
+        );
+        assert!(!result.success);
+        assert_eq!("PermissionDenied", result.error_kind);
+
+        let result = ipfs.name_publish_cp(
+            "QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR1n".to_string(),
+            "".to_string(),
+            "".to_string(),
+            "".to_string(),
+            non_admin_call_parameters(),
+        );
+        assert!(!result.success);
+        assert_eq!("PermissionDenied", result.error_kind);
+    }
+
+    #[test]
+    fn key_gen_invalid_key_type() {
+        let (_storage, mut ipfs) = new_service();
+        let result = ipfs.key_gen("key".to_string(), "dsa".to_string());
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+        assert!(result.error.starts_with("invalid key type: dsa"));
+    }
+
+    #[test]
+    fn verify_file_known_cids() {
+        let (_storage, mut ipfs) = new_service();
+        let empty = write_vault_file("verify_empty", b"");
+        let result = ipfs.verify_file(
+            empty,
+            "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH".to_string(),
+            "".to_string(),
+            false,
+        );
+        assert!(result.success, "{}", result.error);
+
+        let hello = write_vault_file("verify_hello", b"hello world\n");
+        let result = ipfs.verify_file(
+            hello.clone(),
+            "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+            "".to_string(),
+            false,
+        );
+        assert!(result.success, "{}", result.error);
+
+        let result = ipfs.verify_file(
+            hello.clone(),
+            "bafkreifjjcie6lypi6ny7amxnfftagclbuxndqonfipmb64f2km2devei4".to_string(),
+            "".to_string(),
+            true,
+        );
+        assert!(result.success, "{}", result.error);
+
+        let result = ipfs.verify_file(
+            hello,
+            "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH".to_string(),
+            "".to_string(),
+            false,
+        );
+        assert!(!result.success);
+        assert_eq!("IntegrityCheckFailed", result.error_kind);
+    }
+
+    #[test]
+    fn verify_file_two_levels_deep() {
+        let (_storage, mut ipfs) = new_service();
+        // 176 chunks of 1 KiB don't fit into 174 links of a single node
+        let contents: Vec<u8> = (0..175 * 1024 + 100).map(|i| (i % 251) as u8).collect();
+        let path = write_vault_file("verify_deep", &contents);
+
+        let result = ipfs.verify_file(
+            path.clone(),
+            "QmXX9jeKmXDQ41PYnqcPzkLH82e54zLBBZSyLLdgBZVivU".to_string(),
+            "size-1024".to_string(),
+            false,
+        );
+        assert!(result.success, "{}", result.error);
+
+        let result = ipfs.verify_file(
+            path,
+            "bafybeiaflpmm76a6krraq6ttvswt77dclvo3tlzfmmt2md5cvkbebvzkvy".to_string(),
+            "size-1024".to_string(),
+            true,
+        );
+        assert!(result.success, "{}", result.error);
+    }
+
+    #[test]
+    fn verify_file_unsupported_params() {
+        let (_storage, mut ipfs) = new_service();
+        let path = write_vault_file("verify_params", b"hello world\n");
+        let result = ipfs.verify_file(
+            path,
+            "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o".to_string(),
+            "rabin".to_string(),
+            false,
+        );
+        assert!(!result.success);
+        assert_eq!("InvalidArgument", result.error_kind);
+    }
 }
@@ -0,0 +1,148 @@
+/*
+ * Copyright 2021 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt;
+
+/// Caller is not allowed to perform an action. Message always starts with `permission denied`,
+/// so callers can tell it apart from other errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionDenied {
+    pub peer_id: String,
+    pub action: String,
+}
+
+impl fmt::Display for PermissionDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "permission denied: peer {} is not allowed to {}",
+            self.peer_id, self.action
+        )
+    }
+}
+
+impl std::error::Error for PermissionDenied {}
+
+/// Machine-readable kind of an error, returned in `error_kind` field of every result.
+/// Empty `error_kind` means success.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Required multiaddr is not set in service config.
+    NotConfigured,
+    InvalidMultiaddr,
+    InvalidCid,
+    InvalidArgument,
+    Timeout,
+    PermissionDenied,
+    /// Content, key or IPNS name is not found.
+    NotFound,
+    /// IPFS daemon can't be reached.
+    IpfsUnavailable,
+    /// IPFS call failed for any other reason, regardless of the backend.
+    IpfsCliFailed,
+    Utf8,
+    IntegrityCheckFailed,
+    Other,
+}
+
+const ERROR_KINDS: [ErrorKind; 12] = [
+    ErrorKind::NotConfigured,
+    ErrorKind::InvalidMultiaddr,
+    ErrorKind::InvalidCid,
+    ErrorKind::InvalidArgument,
+    ErrorKind::Timeout,
+    ErrorKind::PermissionDenied,
+    ErrorKind::NotFound,
+    ErrorKind::IpfsUnavailable,
+    ErrorKind::IpfsCliFailed,
+    ErrorKind::Utf8,
+    ErrorKind::IntegrityCheckFailed,
+    ErrorKind::Other,
+];
+
+impl ErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::NotConfigured => "NotConfigured",
+            ErrorKind::InvalidMultiaddr => "InvalidMultiaddr",
+            ErrorKind::InvalidCid => "InvalidCid",
+            ErrorKind::InvalidArgument => "InvalidArgument",
+            ErrorKind::Timeout => "Timeout",
+            ErrorKind::PermissionDenied => "PermissionDenied",
+            ErrorKind::NotFound => "NotFound",
+            ErrorKind::IpfsUnavailable => "IpfsUnavailable",
+            ErrorKind::IpfsCliFailed => "IpfsCliFailed",
+            ErrorKind::Utf8 => "Utf8",
+            ErrorKind::IntegrityCheckFailed => "IntegrityCheckFailed",
+            ErrorKind::Other => "Other",
+        }
+    }
+
+    /// Parse `error_kind` field of a result, unknown values become `Other`.
+    pub fn parse(kind: &str) -> Self {
+        ERROR_KINDS
+            .iter()
+            .find(|k| k.as_str() == kind)
+            .copied()
+            .unwrap_or(ErrorKind::Other)
+    }
+
+    /// Create an error of this kind.
+    pub fn error(self, message: impl Into<String>) -> eyre::Report {
+        eyre::Report::new(IpfsError {
+            kind: self,
+            message: message.into(),
+        })
+    }
+
+    /// Find out kind of an error by looking through its chain of causes.
+    pub fn of(err: &eyre::Report) -> Self {
+        for cause in err.chain() {
+            if let Some(e) = cause.downcast_ref::<IpfsError>() {
+                return e.kind;
+            }
+            if cause.is::<PermissionDenied>() {
+                return ErrorKind::PermissionDenied;
+            }
+            if cause.is::<std::string::FromUtf8Error>() || cause.is::<std::str::Utf8Error>() {
+                return ErrorKind::Utf8;
+            }
+        }
+
+        ErrorKind::Other
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error with a known kind, created by `ErrorKind::error`.
+#[derive(Debug)]
+pub struct IpfsError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl fmt::Display for IpfsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for IpfsError {}
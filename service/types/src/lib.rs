@@ -1,5 +1,18 @@
+mod errors;
+mod options;
 mod results;
 
+pub use errors::{ErrorKind, IpfsError, PermissionDenied};
+pub use options::{IpfsPutOptions, IpfsRetryPolicy};
+
 pub use results::{
-    IpfsGetPeerIdResult, IpfsGetResult, IpfsMultiaddrResult, IpfsPutResult, IpfsResult,
+    IpfsBadPin, IpfsCarInfo, IpfsCarInfoResult, IpfsCarVerifyResult, IpfsCatResult, IpfsCidInfo,
+    IpfsCidInfoResult, IpfsCidResult, IpfsDagImportResult, IpfsGetFromAnyResult,
+    IpfsGetManifestResult, IpfsGetPeerIdResult, IpfsGetResult, IpfsGetStringResult, IpfsKey,
+    IpfsKeyListResult, IpfsKeyResult, IpfsLsEntry, IpfsLsResult, IpfsManifestEntry,
+    IpfsMultiaddrResult, IpfsNamePublishResult, IpfsNameResolveResult, IpfsPeeringLsResult,
+    IpfsPeeringPeer, IpfsPin, IpfsPinLsResult, IpfsPinVerifyResult, IpfsPutResult,
+    IpfsRepoGcResult, IpfsRepoStat, IpfsRepoStatResult, IpfsRepoVerifyResult, IpfsResult,
+    IpfsSourceError, IpfsStat, IpfsStatResult, IpfsSwarmPeer, IpfsSwarmPeersResult, IpfsVaultUsage,
+    IpfsVaultUsageResult,
 };
@@ -0,0 +1,65 @@
+/*
+ * Copyright 2021 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use marine_rs_sdk::marine;
+
+/// Parameters of `ipfs add`. All of them are always passed explicitly,
+/// so the resulting CID doesn't depend on defaults of the IPFS node.
+#[marine]
+#[derive(Clone, Debug, PartialEq)]
+pub struct IpfsPutOptions {
+    /// CID version, 0 or 1.
+    pub cid_version: u32,
+    /// Multihash function, e.g. "sha2-256" or "blake2b-256".
+    pub hash: String,
+    /// Chunking algorithm, e.g. "size-262144" or "rabin-262144-524288-1048576".
+    pub chunker: String,
+    /// Use raw blocks for leaf nodes.
+    pub raw_leaves: bool,
+    /// Use trickle-dag layout instead of balanced one.
+    pub trickle: bool,
+    /// Inline small blocks into CIDs.
+    pub inline: bool,
+    /// Only calculate the hash, don't write blocks to the datastore.
+    pub only_hash: bool,
+}
+
+impl IpfsPutOptions {
+    /// Options as `ipfs add` flags.
+    pub fn to_args(&self) -> Vec<(String, String)> {
+        vec![
+            ("cid-version".to_string(), self.cid_version.to_string()),
+            ("hash".to_string(), self.hash.clone()),
+            ("chunker".to_string(), self.chunker.clone()),
+            ("raw-leaves".to_string(), self.raw_leaves.to_string()),
+            ("trickle".to_string(), self.trickle.to_string()),
+            ("inline".to_string(), self.inline.to_string()),
+            ("only-hash".to_string(), self.only_hash.to_string()),
+        ]
+    }
+}
+
+/// Policy of retrying failed calls to IPFS.
+#[marine]
+#[derive(Clone, Debug, PartialEq)]
+pub struct IpfsRetryPolicy {
+    /// Maximum number of attempts, 1 disables retries.
+    pub max_attempts: u32,
+    /// Delay before the second attempt, doubled before every next one.
+    pub base_delay_ms: u64,
+    /// Error kinds worth retrying, e.g. "Timeout" or "IpfsUnavailable".
+    pub retryable_error_kinds: Vec<String>,
+}
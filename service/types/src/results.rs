@@ -14,6 +14,8 @@
  * limitations under the License.
  */
 
+use crate::ErrorKind;
+
 use eyre::Result;
 use marine_rs_sdk::marine;
 
@@ -21,6 +23,9 @@ use marine_rs_sdk::marine;
 pub struct IpfsResult {
     pub success: bool,
     pub error: String,
+    pub error_kind: String,
+    /// Number of calls to IPFS made, greater than 1 if the call was retried.
+    pub attempts: u32,
 }
 
 impl From<Result<()>> for IpfsResult {
@@ -29,10 +34,14 @@ impl From<Result<()>> for IpfsResult {
             Ok(_) => Self {
                 success: true,
                 error: "".to_string(),
+                error_kind: "".to_string(),
+                attempts: 1,
             },
             Err(err) => Self {
                 success: false,
                 error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                attempts: 1,
             },
         }
     }
@@ -42,7 +51,9 @@ impl From<Result<()>> for IpfsResult {
 pub struct IpfsGetResult {
     pub success: bool,
     pub error: String,
+    pub error_kind: String,
     pub path: String,
+    pub attempts: u32,
 }
 
 impl From<Result<String>> for IpfsGetResult {
@@ -51,12 +62,16 @@ impl From<Result<String>> for IpfsGetResult {
             Ok(path) => Self {
                 success: true,
                 error: "".to_string(),
+                error_kind: "".to_string(),
                 path,
+                attempts: 1,
             },
             Err(err) => Self {
                 success: false,
                 error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
                 path: "".to_string(),
+                attempts: 1,
             },
         }
     }
@@ -66,7 +81,9 @@ impl From<Result<String>> for IpfsGetResult {
 pub struct IpfsPutResult {
     pub success: bool,
     pub error: String,
+    pub error_kind: String,
     pub hash: String,
+    pub attempts: u32,
 }
 
 impl From<Result<String>> for IpfsPutResult {
@@ -75,12 +92,16 @@ impl From<Result<String>> for IpfsPutResult {
             Ok(hash) => Self {
                 success: true,
                 error: "".to_string(),
+                error_kind: "".to_string(),
                 hash,
+                attempts: 1,
             },
             Err(err) => Self {
                 success: false,
                 error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
                 hash: "".to_string(),
+                attempts: 1,
             },
         }
     }
@@ -90,6 +111,7 @@ impl From<Result<String>> for IpfsPutResult {
 pub struct IpfsGetPeerIdResult {
     pub success: bool,
     pub error: String,
+    pub error_kind: String,
     pub peer_id: String,
 }
 
@@ -99,11 +121,13 @@ impl From<Result<String>> for IpfsGetPeerIdResult {
             Ok(peer_id) => Self {
                 success: true,
                 error: "".to_string(),
+                error_kind: "".to_string(),
                 peer_id,
             },
             Err(err) => Self {
                 success: false,
                 error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
                 peer_id: "".to_string(),
             },
         }
@@ -114,6 +138,7 @@ impl From<Result<String>> for IpfsGetPeerIdResult {
 pub struct IpfsMultiaddrResult {
     pub success: bool,
     pub error: String,
+    pub error_kind: String,
     pub multiaddr: String,
 }
 
@@ -123,13 +148,833 @@ impl From<Result<String>> for IpfsMultiaddrResult {
             Ok(multiaddr) => Self {
                 success: true,
                 error: "".to_string(),
+                error_kind: "".to_string(),
                 multiaddr,
             },
             Err(err) => Self {
                 success: false,
                 error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
                 multiaddr: "".to_string(),
             },
         }
     }
 }
+
+#[marine]
+pub struct IpfsPin {
+    pub hash: String,
+    pub pin_type: String,
+}
+
+#[marine]
+pub struct IpfsPinLsResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub pins: Vec<IpfsPin>,
+}
+
+impl From<Result<Vec<IpfsPin>>> for IpfsPinLsResult {
+    fn from(result: Result<Vec<IpfsPin>>) -> Self {
+        match result {
+            Ok(pins) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                pins,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                pins: vec![],
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsBadPin {
+    pub hash: String,
+    pub bad_nodes: Vec<String>,
+}
+
+#[marine]
+pub struct IpfsPinVerifyResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub bad_pins: Vec<IpfsBadPin>,
+}
+
+impl From<Result<Vec<IpfsBadPin>>> for IpfsPinVerifyResult {
+    fn from(result: Result<Vec<IpfsBadPin>>) -> Self {
+        match result {
+            Ok(bad_pins) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                bad_pins,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                bad_pins: vec![],
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsSwarmPeer {
+    pub peer_id: String,
+    pub addr: String,
+    /// Latency as reported by IPFS, e.g. "12.5ms", empty if unknown.
+    pub latency: String,
+    /// "inbound", "outbound" or "unknown".
+    pub direction: String,
+}
+
+#[marine]
+pub struct IpfsSwarmPeersResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub peers: Vec<IpfsSwarmPeer>,
+}
+
+impl From<Result<Vec<IpfsSwarmPeer>>> for IpfsSwarmPeersResult {
+    fn from(result: Result<Vec<IpfsSwarmPeer>>) -> Self {
+        match result {
+            Ok(peers) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                peers,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                peers: vec![],
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsPeeringPeer {
+    pub peer_id: String,
+    pub addrs: Vec<String>,
+}
+
+#[marine]
+pub struct IpfsPeeringLsResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub peers: Vec<IpfsPeeringPeer>,
+}
+
+impl From<Result<Vec<IpfsPeeringPeer>>> for IpfsPeeringLsResult {
+    fn from(result: Result<Vec<IpfsPeeringPeer>>) -> Self {
+        match result {
+            Ok(peers) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                peers,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                peers: vec![],
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsNamePublishResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub name: String,
+    pub value: String,
+}
+
+impl From<Result<(String, String)>> for IpfsNamePublishResult {
+    fn from(result: Result<(String, String)>) -> Self {
+        match result {
+            Ok((name, value)) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                name,
+                value,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                name: "".to_string(),
+                value: "".to_string(),
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsNameResolveResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub path: String,
+}
+
+impl From<Result<String>> for IpfsNameResolveResult {
+    fn from(result: Result<String>) -> Self {
+        match result {
+            Ok(path) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                path,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                path: "".to_string(),
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsKey {
+    pub name: String,
+    pub id: String,
+}
+
+#[marine]
+pub struct IpfsKeyResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub name: String,
+    pub id: String,
+}
+
+impl From<Result<IpfsKey>> for IpfsKeyResult {
+    fn from(result: Result<IpfsKey>) -> Self {
+        match result {
+            Ok(key) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                name: key.name,
+                id: key.id,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                name: "".to_string(),
+                id: "".to_string(),
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsKeyListResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub keys: Vec<IpfsKey>,
+}
+
+impl From<Result<Vec<IpfsKey>>> for IpfsKeyListResult {
+    fn from(result: Result<Vec<IpfsKey>>) -> Self {
+        match result {
+            Ok(keys) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                keys,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                keys: vec![],
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsCatResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub contents: Vec<u8>,
+}
+
+impl From<Result<Vec<u8>>> for IpfsCatResult {
+    fn from(result: Result<Vec<u8>>) -> Self {
+        match result {
+            Ok(contents) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                contents,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                contents: vec![],
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsGetStringResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub contents: String,
+}
+
+impl From<Result<String>> for IpfsGetStringResult {
+    fn from(result: Result<String>) -> Self {
+        match result {
+            Ok(contents) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                contents,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                contents: "".to_string(),
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsCidResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub cid: String,
+}
+
+impl From<Result<String>> for IpfsCidResult {
+    fn from(result: Result<String>) -> Self {
+        match result {
+            Ok(cid) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                cid,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                cid: "".to_string(),
+            },
+        }
+    }
+}
+
+pub struct IpfsCidInfo {
+    pub version: u64,
+    pub codec: String,
+    pub multihash: String,
+    pub digest_size: u32,
+    pub multibase: String,
+}
+
+#[marine]
+pub struct IpfsCidInfoResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub version: u64,
+    pub codec: String,
+    pub multihash: String,
+    pub digest_size: u32,
+    pub multibase: String,
+}
+
+impl From<Result<IpfsCidInfo>> for IpfsCidInfoResult {
+    fn from(result: Result<IpfsCidInfo>) -> Self {
+        match result {
+            Ok(info) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                version: info.version,
+                codec: info.codec,
+                multihash: info.multihash,
+                digest_size: info.digest_size,
+                multibase: info.multibase,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                version: 0,
+                codec: "".to_string(),
+                multihash: "".to_string(),
+                digest_size: 0,
+                multibase: "".to_string(),
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsCarInfo {
+    pub version: u64,
+    pub roots: Vec<String>,
+    pub blocks: Vec<String>,
+}
+
+#[marine]
+pub struct IpfsCarInfoResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub version: u64,
+    pub roots: Vec<String>,
+    pub blocks: Vec<String>,
+}
+
+impl From<Result<IpfsCarInfo>> for IpfsCarInfoResult {
+    fn from(result: Result<IpfsCarInfo>) -> Self {
+        match result {
+            Ok(info) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                version: info.version,
+                roots: info.roots,
+                blocks: info.blocks,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                version: 0,
+                roots: vec![],
+                blocks: vec![],
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsCarVerifyResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub roots: Vec<String>,
+    pub block_count: u64,
+    pub total_size: u64,
+}
+
+impl From<Result<(Vec<String>, u64, u64)>> for IpfsCarVerifyResult {
+    fn from(result: Result<(Vec<String>, u64, u64)>) -> Self {
+        match result {
+            Ok((roots, block_count, total_size)) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                roots,
+                block_count,
+                total_size,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                roots: vec![],
+                block_count: 0,
+                total_size: 0,
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsDagImportResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub roots: Vec<String>,
+    pub attempts: u32,
+}
+
+impl From<Result<Vec<String>>> for IpfsDagImportResult {
+    fn from(result: Result<Vec<String>>) -> Self {
+        match result {
+            Ok(roots) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                roots,
+                attempts: 1,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                roots: vec![],
+                attempts: 1,
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsLsEntry {
+    pub name: String,
+    pub hash: String,
+    pub size: u64,
+    /// One of `file`, `directory`, `symlink` or `unknown`.
+    pub entry_type: String,
+}
+
+#[marine]
+pub struct IpfsLsResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub entries: Vec<IpfsLsEntry>,
+    /// Set if the directory has more entries than were requested.
+    pub truncated: bool,
+}
+
+impl From<Result<(Vec<IpfsLsEntry>, bool)>> for IpfsLsResult {
+    fn from(result: Result<(Vec<IpfsLsEntry>, bool)>) -> Self {
+        match result {
+            Ok((entries, truncated)) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                entries,
+                truncated,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                entries: vec![],
+                truncated: false,
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsManifestEntry {
+    /// Path relative to the downloaded root, empty for the root itself.
+    pub path: String,
+    pub hash: String,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+#[marine]
+pub struct IpfsSourceError {
+    pub multiaddr: String,
+    pub error: String,
+    pub error_kind: String,
+}
+
+#[marine]
+pub struct IpfsGetFromAnyResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub path: String,
+    /// Multiaddr of the source that served the content.
+    pub source: String,
+    /// Errors of the sources that failed, in the order they were tried.
+    pub source_errors: Vec<IpfsSourceError>,
+}
+
+impl From<Result<(String, String)>> for IpfsGetFromAnyResult {
+    fn from(result: Result<(String, String)>) -> Self {
+        match result {
+            Ok((path, source)) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                path,
+                source,
+                source_errors: vec![],
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                path: "".to_string(),
+                source: "".to_string(),
+                source_errors: vec![],
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsGetManifestResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub path: String,
+    pub entries: Vec<IpfsManifestEntry>,
+}
+
+impl From<Result<(String, Vec<IpfsManifestEntry>)>> for IpfsGetManifestResult {
+    fn from(result: Result<(String, Vec<IpfsManifestEntry>)>) -> Self {
+        match result {
+            Ok((path, entries)) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                path,
+                entries,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                path: "".to_string(),
+                entries: vec![],
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsStat {
+    pub hash: String,
+    pub size: u64,
+    /// Size of the object along with all the objects it links to.
+    pub cumulative_size: u64,
+    pub blocks: u64,
+    /// "file" or "directory" for UnixFS objects, "dag" for other DAGs.
+    pub entry_type: String,
+    /// Whether the whole DAG is in the repo of the queried node, which is the node
+    /// at `external_multiaddr` for `stat_from`. Always false for "dag" objects,
+    /// their availability isn't checked.
+    pub local: bool,
+    /// Cumulative size of the blocks that are already in the repo of the queried node.
+    pub size_local: u64,
+}
+
+#[marine]
+pub struct IpfsStatResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub hash: String,
+    pub size: u64,
+    pub cumulative_size: u64,
+    pub blocks: u64,
+    pub entry_type: String,
+    pub local: bool,
+    pub size_local: u64,
+}
+
+impl From<Result<IpfsStat>> for IpfsStatResult {
+    fn from(result: Result<IpfsStat>) -> Self {
+        match result {
+            Ok(stat) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                hash: stat.hash,
+                size: stat.size,
+                cumulative_size: stat.cumulative_size,
+                blocks: stat.blocks,
+                entry_type: stat.entry_type,
+                local: stat.local,
+                size_local: stat.size_local,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                hash: "".to_string(),
+                size: 0,
+                cumulative_size: 0,
+                blocks: 0,
+                entry_type: "".to_string(),
+                local: false,
+                size_local: 0,
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsVaultUsage {
+    pub particle_bytes: u64,
+    pub peer_bytes: u64,
+    pub total_bytes: u64,
+}
+
+#[marine]
+pub struct IpfsVaultUsageResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    /// Bytes written to the vault of the current particle.
+    pub particle_bytes: u64,
+    /// Bytes written on behalf of the init peer of the current particle across all particles.
+    pub peer_bytes: u64,
+    /// Bytes written to vaults of all particles that still exist.
+    pub total_bytes: u64,
+}
+
+impl From<Result<IpfsVaultUsage>> for IpfsVaultUsageResult {
+    fn from(result: Result<IpfsVaultUsage>) -> Self {
+        match result {
+            Ok(usage) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                particle_bytes: usage.particle_bytes,
+                peer_bytes: usage.peer_bytes,
+                total_bytes: usage.total_bytes,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                particle_bytes: 0,
+                peer_bytes: 0,
+                total_bytes: 0,
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsRepoStat {
+    /// Size of the repo in bytes.
+    pub repo_size: u64,
+    /// Maximum size of the repo from IPFS config, gc is triggered when it's reached.
+    pub storage_max: u64,
+    pub num_objects: u64,
+    pub repo_path: String,
+    pub version: String,
+}
+
+#[marine]
+pub struct IpfsRepoStatResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub repo_size: u64,
+    pub storage_max: u64,
+    pub num_objects: u64,
+    pub repo_path: String,
+    pub version: String,
+}
+
+impl From<Result<IpfsRepoStat>> for IpfsRepoStatResult {
+    fn from(result: Result<IpfsRepoStat>) -> Self {
+        match result {
+            Ok(stat) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                repo_size: stat.repo_size,
+                storage_max: stat.storage_max,
+                num_objects: stat.num_objects,
+                repo_path: stat.repo_path,
+                version: stat.version,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                repo_size: 0,
+                storage_max: 0,
+                num_objects: 0,
+                repo_path: "".to_string(),
+                version: "".to_string(),
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsRepoGcResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    /// Number of blocks removed from the repo.
+    pub removed: u64,
+}
+
+impl From<Result<u64>> for IpfsRepoGcResult {
+    fn from(result: Result<u64>) -> Self {
+        match result {
+            Ok(removed) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                removed,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                removed: 0,
+            },
+        }
+    }
+}
+
+#[marine]
+pub struct IpfsRepoVerifyResult {
+    pub success: bool,
+    pub error: String,
+    pub error_kind: String,
+    pub blocks_verified: u64,
+}
+
+impl From<Result<u64>> for IpfsRepoVerifyResult {
+    fn from(result: Result<u64>) -> Self {
+        match result {
+            Ok(blocks_verified) => Self {
+                success: true,
+                error: "".to_string(),
+                error_kind: "".to_string(),
+                blocks_verified,
+            },
+            Err(err) => Self {
+                success: false,
+                error: err.to_string(),
+                error_kind: ErrorKind::of(&err).to_string(),
+                blocks_verified: 0,
+            },
+        }
+    }
+}